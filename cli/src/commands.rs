@@ -0,0 +1,386 @@
+use crate::context::Context;
+use crate::{Cli, Command};
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar;
+use anchor_lang::Discriminator;
+use anyhow::{bail, Result};
+use meteora_fee_routing::dlmm_integration::meteora_dlmm_types::{
+    Position, PositionV2, DLMM_EVENT_AUTHORITY, DLMM_PROGRAM_ID, MEMO_PROGRAM_ID,
+};
+use meteora_fee_routing::{DistributionConfig, FeeVault, ProgramConfig, MAX_POSITIONS_PER_BATCH};
+use meteora_fee_routing_client::instructions::{self, PositionAccounts, RecipientTokenAccounts};
+use meteora_fee_routing_client::rpc::AccountFilter;
+use meteora_fee_routing_client::state::{self, AccountSource};
+use meteora_fee_routing_client::{accounts, pda};
+use serde_json::{json, Value};
+
+/// Offsets of `lb_pair` and `owner` in both DLMM position layouts, after the discriminator
+const POSITION_LB_PAIR_OFFSET: usize = 8;
+const POSITION_OWNER_OFFSET: usize = 8 + 32;
+
+pub fn run(cli: Cli) -> Result<()> {
+    let mut ctx = Context::new(&cli.url, cli.keypair, cli.output, cli.dry_run)?;
+
+    match cli.command {
+        Command::Init { pool } => init(&mut ctx, pool),
+        Command::Collect { vault, positions } => collect(&mut ctx, vault, positions),
+        Command::Distribute { vault } => distribute(&mut ctx, vault),
+        Command::Show { vault } => show(&ctx, vault),
+        Command::SetAuthority { vault, new_authority, accept } => match new_authority {
+            Some(new_authority) => propose_authority(&mut ctx, vault, new_authority),
+            None if accept => accept_authority(&mut ctx, vault),
+            None => bail!("pass a new authority or --accept"),
+        },
+        Command::List { pool } => list(&ctx, pool),
+        Command::Migrate { pool } => migrate(&mut ctx, pool),
+    }
+}
+
+fn init(ctx: &mut Context, pool: Pubkey) -> Result<()> {
+    let authority = ctx.signer_pubkey()?;
+    let lb_pair = state::fetch_lb_pair(&ctx.rpc, &pool)?;
+    let program_config: ProgramConfig = state::fetch(&ctx.rpc, &pda::program_config().0)?;
+    let (fee_vault, bump) = pda::fee_vault(&pool, &authority);
+
+    let base_mint = lb_pair.token_x_mint;
+    let quote_mint = lb_pair.token_y_mint;
+    let base_token_program = token_program_of(ctx, &base_mint)?;
+    let quote_token_program = token_program_of(ctx, &quote_mint)?;
+
+    let ix = instructions::initialize_fee_vault(
+        accounts::InitializeFeeVault {
+            fee_vault,
+            dlmm_pool: pool,
+            base_mint,
+            quote_mint,
+            base_token_account: pda::vault_token_account(&fee_vault, &base_mint, &base_token_program),
+            quote_token_account: pda::vault_token_account(&fee_vault, &quote_mint, &quote_token_program),
+            program_config: pda::program_config().0,
+            vault_registry: pda::vault_registry(program_config.current_registry_page()).0,
+            authority,
+            system_program: anchor_lang::system_program::ID,
+            base_token_program,
+            quote_token_program,
+            associated_token_program: anchor_spl::associated_token::ID,
+            rent: sysvar::rent::ID,
+        },
+        bump,
+    );
+
+    ctx.submit(
+        &[ix],
+        json!({
+            "fee_vault": fee_vault.to_string(),
+            "dlmm_pool": pool.to_string(),
+            "authority": authority.to_string(),
+        }),
+    )
+}
+
+fn collect(ctx: &mut Context, vault: Pubkey, positions: Vec<Pubkey>) -> Result<()> {
+    let collector = ctx.signer_pubkey()?;
+    let fee_vault = state::fetch_fee_vault(&ctx.rpc, &vault)?;
+    let lb_pair = state::fetch_lb_pair(&ctx.rpc, &fee_vault.dlmm_pool)?;
+    let program_config: ProgramConfig = state::fetch(&ctx.rpc, &pda::program_config().0)?;
+
+    let positions = if positions.is_empty() {
+        vault_positions(ctx, &vault, &fee_vault.dlmm_pool)?
+    } else {
+        positions
+    };
+    if positions.is_empty() {
+        bail!("fee vault {vault} owns no DLMM positions");
+    }
+
+    let position_accounts = positions
+        .iter()
+        .map(|position| {
+            let account = state::fetch_position(&ctx.rpc, position)?;
+            let decoded = state::decode_position(&account.data)?;
+            Ok(PositionAccounts {
+                position: *position,
+                bin_arrays: pda::dlmm_bin_arrays(&fee_vault.dlmm_pool, decoded.lower_bin_id(), decoded.upper_bin_id()),
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    // Only registered positions can be collected from, so register any new ones first
+    for position in &positions {
+        let vault_position = pda::vault_position(&vault, position).0;
+        if ctx.rpc.get_account(&vault_position)?.is_some() {
+            continue;
+        }
+
+        let ix = instructions::register_vault_position(accounts::RegisterVaultPosition {
+            vault_position,
+            fee_vault: vault,
+            position: *position,
+            dlmm_program: DLMM_PROGRAM_ID,
+            program_config: pda::program_config().0,
+            payer: collector,
+            system_program: anchor_lang::system_program::ID,
+        });
+        ctx.submit(&[ix], json!({ "fee_vault": vault.to_string(), "registered": position.to_string() }))?;
+    }
+
+    // The protocol fee goes to the treasury's ATAs, which are only needed while it is set
+    let treasury_account = |mint: &Pubkey, token_program: &Pubkey| {
+        (program_config.protocol_fee_bps > 0)
+            .then(|| pda::vault_token_account(&program_config.treasury, mint, token_program))
+    };
+
+    for batch in position_accounts.chunks(MAX_POSITIONS_PER_BATCH) {
+        let ix = instructions::collect_many_position_fees(
+            accounts::CollectManyPositionFees {
+                fee_vault: vault,
+                lb_pair: fee_vault.dlmm_pool,
+                base_token_account: pda::vault_token_account(&vault, &fee_vault.base_mint, &fee_vault.base_token_program),
+                quote_token_account: pda::vault_token_account(&vault, &fee_vault.quote_mint, &fee_vault.quote_token_program),
+                reserve_x: lb_pair.reserve_x,
+                reserve_y: lb_pair.reserve_y,
+                base_mint: fee_vault.base_mint,
+                quote_mint: fee_vault.quote_mint,
+                collector,
+                base_token_program: fee_vault.base_token_program,
+                quote_token_program: fee_vault.quote_token_program,
+                memo_program: MEMO_PROGRAM_ID,
+                event_authority: DLMM_EVENT_AUTHORITY,
+                dlmm_program: DLMM_PROGRAM_ID,
+                program_config: pda::program_config().0,
+                treasury_base_account: treasury_account(&fee_vault.base_mint, &fee_vault.base_token_program),
+                treasury_quote_account: treasury_account(&fee_vault.quote_mint, &fee_vault.quote_token_program),
+            },
+            batch,
+            &[],
+        )?;
+
+        let positions: Vec<String> = batch.iter().map(|accounts| accounts.position.to_string()).collect();
+        ctx.submit(&[ix], json!({ "fee_vault": vault.to_string(), "positions": positions }))?;
+    }
+
+    Ok(())
+}
+
+fn distribute(ctx: &mut Context, vault: Pubkey) -> Result<()> {
+    let caller = ctx.signer_pubkey()?;
+    let fee_vault = state::fetch_fee_vault(&ctx.rpc, &vault)?;
+    let distribution_config_address = pda::distribution_config(&vault).0;
+    let distribution_config: DistributionConfig = state::fetch(&ctx.rpc, &distribution_config_address)?;
+
+    let recipients: Vec<RecipientTokenAccounts> = distribution_config
+        .recipients
+        .iter()
+        .map(|entry| RecipientTokenAccounts {
+            base: pda::vault_token_account(&entry.recipient, &fee_vault.base_mint, &fee_vault.base_token_program),
+            quote: pda::vault_token_account(&entry.recipient, &fee_vault.quote_mint, &fee_vault.quote_token_program),
+        })
+        .collect();
+
+    let ix = instructions::distribute_to_recipients(
+        accounts::DistributeToRecipients {
+            fee_vault: vault,
+            distribution_config: distribution_config_address,
+            base_mint: fee_vault.base_mint,
+            quote_mint: fee_vault.quote_mint,
+            base_token_account: pda::vault_token_account(&vault, &fee_vault.base_mint, &fee_vault.base_token_program),
+            quote_token_account: pda::vault_token_account(&vault, &fee_vault.quote_mint, &fee_vault.quote_token_program),
+            caller,
+            base_token_program: fee_vault.base_token_program,
+            quote_token_program: fee_vault.quote_token_program,
+        },
+        &recipients,
+        &[],
+    );
+
+    let recipients: Vec<Value> = distribution_config
+        .recipients
+        .iter()
+        .map(|entry| json!({ "recipient": entry.recipient.to_string(), "weight_bps": entry.weight_bps }))
+        .collect();
+    ctx.submit(&[ix], json!({ "fee_vault": vault.to_string(), "recipients": recipients }))
+}
+
+fn show(ctx: &Context, vault: Pubkey) -> Result<()> {
+    let account = state::fetch_raw(&ctx.rpc, &vault, &meteora_fee_routing::ID)?;
+    if state::is_v0_fee_vault(&account.data) {
+        let fee_vault = state::decode_fee_vault_v0(&account.data)?;
+        return ctx.print(json!({
+            "address": vault.to_string(),
+            "version": 0,
+            "dlmm_pool": fee_vault.dlmm_pool.to_string(),
+            "authority": fee_vault.authority.to_string(),
+            "base_mint": fee_vault.base_mint.to_string(),
+            "quote_mint": fee_vault.quote_mint.to_string(),
+            "creation_time": fee_vault.creation_time,
+            "total_collected_base": fee_vault.total_collected_base,
+            "total_collected_quote": fee_vault.total_collected_quote,
+            "needs_migration": true,
+        }));
+    }
+
+    let fee_vault = state::decode_fee_vault(&account.data)?;
+    let mut fields = fee_vault_json(&vault, &fee_vault);
+    fields["needs_migration"] = false.into();
+    ctx.print(fields)
+}
+
+fn migrate(ctx: &mut Context, pool: Pubkey) -> Result<()> {
+    let authority = ctx.signer_pubkey()?;
+    let fee_vault = pda::legacy_fee_vault(&pool).0;
+    let legacy = state::decode_fee_vault_v0(&state::fetch_raw(&ctx.rpc, &fee_vault, &meteora_fee_routing::ID)?.data)?;
+    if legacy.authority != authority {
+        bail!("fee vault {fee_vault} has authority {}, not the signer", legacy.authority);
+    }
+    let program_config: ProgramConfig = state::fetch(&ctx.rpc, &pda::program_config().0)?;
+
+    let ix = instructions::migrate_fee_vault(accounts::MigrateFeeVault {
+        fee_vault,
+        dlmm_pool: pool,
+        program_config: pda::program_config().0,
+        vault_registry: pda::vault_registry(program_config.current_registry_page()).0,
+        authority,
+        payer: authority,
+        system_program: anchor_lang::system_program::ID,
+    });
+
+    ctx.submit(&[ix], json!({ "fee_vault": fee_vault.to_string(), "dlmm_pool": pool.to_string() }))
+}
+
+fn propose_authority(ctx: &mut Context, vault: Pubkey, new_authority: Pubkey) -> Result<()> {
+    let authority = ctx.signer_pubkey()?;
+    let ix = instructions::propose_authority(
+        accounts::UpdateFeeVault { fee_vault: vault, authority },
+        new_authority,
+        &[],
+    );
+
+    ctx.submit(
+        &[ix],
+        json!({ "fee_vault": vault.to_string(), "pending_authority": new_authority.to_string() }),
+    )
+}
+
+fn accept_authority(ctx: &mut Context, vault: Pubkey) -> Result<()> {
+    let pending_authority = ctx.signer_pubkey()?;
+    let ix = instructions::accept_authority(
+        accounts::AcceptAuthority { fee_vault: vault, pending_authority },
+        &[],
+    );
+
+    ctx.submit(
+        &[ix],
+        json!({ "fee_vault": vault.to_string(), "authority": pending_authority.to_string() }),
+    )
+}
+
+fn list(ctx: &Context, pool: Option<Pubkey>) -> Result<()> {
+    let vaults: Vec<(Pubkey, Option<FeeVault>)> = match pool {
+        // v0 vaults share the discriminator and the `dlmm_pool` offset, so the
+        // size keeps them out until they are migrated
+        Some(pool) => ctx
+            .rpc
+            .get_program_accounts(
+                &meteora_fee_routing::ID,
+                &[
+                    AccountFilter::DataSize(FeeVault::LEN as u64),
+                    AccountFilter::Memcmp { offset: 0, bytes: FeeVault::DISCRIMINATOR.to_vec() },
+                    AccountFilter::Memcmp { offset: FeeVault::DLMM_POOL_OFFSET, bytes: pool.to_bytes().to_vec() },
+                ],
+            )?
+            .into_iter()
+            .map(|(address, account)| Ok((address, Some(state::decode_fee_vault(&account.data)?))))
+            .collect::<Result<_>>()?,
+        // Closing a vault takes it off the registry, so a missing account is only
+        // reported rather than failing the listing
+        None => state::registered_fee_vaults(&ctx.rpc)?
+            .into_iter()
+            .map(|address| {
+                let fee_vault = match ctx.rpc.get_account(&address)? {
+                    Some(account) => Some(state::decode_fee_vault(&account.data)?),
+                    None => None,
+                };
+                Ok((address, fee_vault))
+            })
+            .collect::<Result<_>>()?,
+    };
+
+    let vaults: Vec<Value> = vaults
+        .iter()
+        .map(|(address, fee_vault)| match fee_vault {
+            Some(fee_vault) => json!({
+                "address": address.to_string(),
+                "dlmm_pool": fee_vault.dlmm_pool.to_string(),
+                "authority": fee_vault.authority.to_string(),
+                "creator": fee_vault.creator.to_string(),
+            }),
+            None => json!({ "address": address.to_string(), "closed": true }),
+        })
+        .collect();
+    ctx.print(json!({ "vaults": vaults }))
+}
+
+/// The token program that owns `mint`
+fn token_program_of(ctx: &Context, mint: &Pubkey) -> Result<Pubkey> {
+    match ctx.rpc.get_account(mint)? {
+        Some(account) => Ok(account.owner),
+        None => bail!("mint {mint} not found"),
+    }
+}
+
+/// Every DLMM position owned by the fee vault
+/// The vault's positions in its pool, one query per DLMM position layout so
+/// the node only scans accounts with that discriminator
+fn vault_positions(ctx: &Context, vault: &Pubkey, lb_pair: &Pubkey) -> Result<Vec<Pubkey>> {
+    let mut positions = Vec::new();
+    for discriminator in [Position::DISCRIMINATOR, PositionV2::DISCRIMINATOR] {
+        let accounts = ctx.rpc.get_program_accounts(
+            &DLMM_PROGRAM_ID,
+            &[
+                AccountFilter::Memcmp { offset: 0, bytes: discriminator.to_vec() },
+                AccountFilter::Memcmp { offset: POSITION_LB_PAIR_OFFSET, bytes: lb_pair.to_bytes().to_vec() },
+                AccountFilter::Memcmp { offset: POSITION_OWNER_OFFSET, bytes: vault.to_bytes().to_vec() },
+            ],
+        )?;
+        positions.extend(accounts.into_iter().map(|(address, _)| address));
+    }
+
+    Ok(positions)
+}
+
+fn fee_vault_json(address: &Pubkey, fee_vault: &FeeVault) -> Value {
+    let key = |key: &Pubkey| key.to_string();
+    let optional = |key: &Option<Pubkey>| key.as_ref().map(Pubkey::to_string);
+
+    json!({
+        "address": key(address),
+        "version": fee_vault.version,
+        "dlmm_pool": key(&fee_vault.dlmm_pool),
+        "authority": key(&fee_vault.authority),
+        "creator": key(&fee_vault.creator),
+        "pending_authority": optional(&fee_vault.pending_authority),
+        "distributor": optional(&fee_vault.distributor),
+        "guardian": optional(&fee_vault.guardian),
+        "config_manager": optional(&fee_vault.config_manager),
+        "base_mint": key(&fee_vault.base_mint),
+        "quote_mint": key(&fee_vault.quote_mint),
+        "base_token_program": key(&fee_vault.base_token_program),
+        "quote_token_program": key(&fee_vault.quote_token_program),
+        "creation_time": fee_vault.creation_time,
+        "total_collected_base": fee_vault.total_collected_base,
+        "total_collected_quote": fee_vault.total_collected_quote,
+        "total_collected_gross_base": fee_vault.total_collected_gross_base,
+        "total_collected_gross_quote": fee_vault.total_collected_gross_quote,
+        "total_protocol_fees_base": fee_vault.total_protocol_fees_base,
+        "total_protocol_fees_quote": fee_vault.total_protocol_fees_quote,
+        "total_skimmed_base": fee_vault.total_skimmed_base,
+        "total_skimmed_quote": fee_vault.total_skimmed_quote,
+        "reward_mints": fee_vault.reward_mints.iter().map(key).collect::<Vec<_>>(),
+        "total_collected_rewards": fee_vault.total_collected_rewards,
+        "total_shares": fee_vault.total_shares,
+        "reserved_base": fee_vault.reserved_base,
+        "reserved_quote": fee_vault.reserved_quote,
+        "config_delay": fee_vault.config_delay,
+        "next_change_id": fee_vault.next_change_id,
+        "paused": fee_vault.paused,
+    })
+}
@@ -0,0 +1,153 @@
+use crate::Output;
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::Instruction;
+use anyhow::{anyhow, bail, Context as _, Result};
+use base64::Engine;
+use meteora_fee_routing_client::rpc::RpcClient;
+use serde_json::Value;
+use solana_keypair::{read_keypair_file, Keypair};
+use solana_signer::Signer;
+use solana_transaction::Transaction;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// How long to wait for a sent transaction to be confirmed
+const CONFIRM_ATTEMPTS: u32 = 60;
+const CONFIRM_INTERVAL: Duration = Duration::from_millis(500);
+
+/// RPC connection, signer and output settings shared by every command
+pub struct Context {
+    pub rpc: RpcClient,
+    keypair_path: PathBuf,
+    keypair: Option<Keypair>,
+    output: Output,
+    dry_run: bool,
+}
+
+impl Context {
+    pub fn new(url: &str, keypair: Option<PathBuf>, output: Output, dry_run: bool) -> Result<Self> {
+        let keypair_path = match keypair {
+            Some(path) => path,
+            None => default_keypair_path()?,
+        };
+
+        Ok(Self {
+            rpc: RpcClient::new(rpc_url(url)),
+            keypair_path,
+            keypair: None,
+            output,
+            dry_run,
+        })
+    }
+
+    /// The signing keypair, read on first use so read-only commands work without one
+    pub fn signer(&mut self) -> Result<&Keypair> {
+        if self.keypair.is_none() {
+            self.keypair = Some(read_keypair(&self.keypair_path)?);
+        }
+        Ok(self.keypair.as_ref().expect("keypair was just read"))
+    }
+
+    pub fn signer_pubkey(&mut self) -> Result<Pubkey> {
+        Ok(self.signer()?.pubkey())
+    }
+
+    /// Sign `instructions` with the keypair, then send them or, on a dry run,
+    /// print the serialized transaction. `fields` describe the operation.
+    pub fn submit(&mut self, instructions: &[Instruction], mut fields: Value) -> Result<()> {
+        let blockhash = self.rpc.get_latest_blockhash()?;
+        let signer = self.signer()?;
+        let transaction = Transaction::new_signed_with_payer(instructions, Some(&signer.pubkey()), &[signer], blockhash);
+        let serialized = bincode::serialize(&transaction)?;
+
+        if self.dry_run {
+            fields["transaction"] = base64::engine::general_purpose::STANDARD.encode(&serialized).into();
+            return self.print(fields);
+        }
+
+        let signature = self.rpc.send_transaction(&serialized)?;
+        self.confirm(&signature)?;
+        fields["signature"] = signature.into();
+        self.print(fields)
+    }
+
+    fn confirm(&self, signature: &str) -> Result<()> {
+        for _ in 0..CONFIRM_ATTEMPTS {
+            match self.rpc.get_signature_status(signature)? {
+                Some(Ok(())) => return Ok(()),
+                Some(Err(error)) => bail!("transaction {signature} failed: {error}"),
+                None => std::thread::sleep(CONFIRM_INTERVAL),
+            }
+        }
+        bail!("transaction {signature} was not confirmed in time")
+    }
+
+    /// Print `fields` as one JSON object, or as `key: value` lines
+    pub fn print(&self, fields: Value) -> Result<()> {
+        match self.output {
+            Output::Json => println!("{}", serde_json::to_string_pretty(&fields)?),
+            Output::Text => print_text(&fields, 0),
+        }
+        Ok(())
+    }
+}
+
+fn print_text(value: &Value, indent: usize) {
+    let pad = " ".repeat(indent);
+    match value {
+        Value::Object(fields) => {
+            for (key, value) in fields {
+                match value {
+                    Value::Object(_) | Value::Array(_) => {
+                        println!("{pad}{key}:");
+                        print_text(value, indent + 2);
+                    }
+                    value => println!("{pad}{key}: {}", text(value)),
+                }
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                match item {
+                    Value::Object(_) | Value::Array(_) => {
+                        println!("{pad}-");
+                        print_text(item, indent + 2);
+                    }
+                    item => println!("{pad}- {}", text(item)),
+                }
+            }
+        }
+        value => println!("{pad}{}", text(value)),
+    }
+}
+
+fn text(value: &Value) -> String {
+    match value {
+        Value::String(value) => value.clone(),
+        Value::Null => "none".to_string(),
+        value => value.to_string(),
+    }
+}
+
+/// Expand the cluster monikers the Solana CLI accepts
+fn rpc_url(url: &str) -> String {
+    match url {
+        "localnet" | "l" => "http://127.0.0.1:8899",
+        "devnet" | "d" => "https://api.devnet.solana.com",
+        "testnet" | "t" => "https://api.testnet.solana.com",
+        "mainnet-beta" | "m" => "https://api.mainnet-beta.solana.com",
+        url => url,
+    }
+    .to_string()
+}
+
+fn default_keypair_path() -> Result<PathBuf> {
+    let home = std::env::var_os("HOME").ok_or_else(|| anyhow!("HOME is not set, pass --keypair"))?;
+    Ok(Path::new(&home).join(".config/solana/id.json"))
+}
+
+fn read_keypair(path: &Path) -> Result<Keypair> {
+    read_keypair_file(path)
+        .map_err(|error| anyhow!("{error}"))
+        .with_context(|| format!("failed to read keypair {}", path.display()))
+}
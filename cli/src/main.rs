@@ -0,0 +1,93 @@
+//! `fee-router`: routine fee vault operations from the command line
+
+mod commands;
+mod context;
+
+use anchor_lang::prelude::Pubkey;
+use clap::{Parser, Subcommand, ValueEnum};
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+#[derive(Parser)]
+#[command(name = "fee-router", version, about = "Operate Meteora fee routing vaults")]
+struct Cli {
+    /// RPC URL, or one of `localnet`, `devnet`, `testnet`, `mainnet-beta`
+    #[arg(short, long, global = true, env = "FEE_ROUTER_URL", default_value = "devnet")]
+    url: String,
+
+    /// Keypair that signs and pays for transactions [default: ~/.config/solana/id.json]
+    #[arg(short, long, global = true, env = "FEE_ROUTER_KEYPAIR")]
+    keypair: Option<PathBuf>,
+
+    #[arg(short, long, global = true, value_enum, default_value_t = Output::Text)]
+    output: Output,
+
+    /// Print the signed, base64 serialized transaction instead of sending it
+    #[arg(long, global = true)]
+    dry_run: bool,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Output {
+    Text,
+    Json,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Create a fee vault for a DLMM pool, with the keypair as authority and creator
+    Init {
+        /// DLMM `LbPair` account
+        pool: Pubkey,
+    },
+    /// Collect fees from the vault's DLMM positions, registering any new ones first
+    Collect {
+        vault: Pubkey,
+        /// Positions to collect from [default: every position the vault owns]
+        #[arg(short, long = "position")]
+        positions: Vec<Pubkey>,
+    },
+    /// Split the vault's balances across its distribution config
+    Distribute { vault: Pubkey },
+    /// Show a fee vault
+    Show { vault: Pubkey },
+    /// Propose a new vault authority, or accept a proposal with `--accept`
+    SetAuthority {
+        vault: Pubkey,
+        /// Authority to propose
+        #[arg(required_unless_present = "accept", conflicts_with = "accept")]
+        new_authority: Option<Pubkey>,
+        /// Accept the pending transfer, signing as the proposed authority
+        #[arg(long)]
+        accept: bool,
+    },
+    /// List fee vaults in the registry, or every vault of one pool
+    List {
+        #[arg(short, long)]
+        pool: Option<Pubkey>,
+    },
+    /// Upgrade a pool's v0 fee vault to the current layout in place, signing as its authority
+    Migrate {
+        /// DLMM `LbPair` account the v0 vault was created for
+        pool: Pubkey,
+    },
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let output = cli.output;
+
+    match commands::run(cli) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            match output {
+                Output::Text => eprintln!("error: {error:#}"),
+                Output::Json => eprintln!("{}", serde_json::json!({ "error": format!("{error:#}") })),
+            }
+            ExitCode::FAILURE
+        }
+    }
+}
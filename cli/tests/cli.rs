@@ -0,0 +1,141 @@
+//! Runs the `fee-router` binary, against a stub RPC node where it needs one.
+
+use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
+use base64::Engine;
+use meteora_fee_routing::instruction;
+use solana_keypair::Keypair;
+use solana_signer::Signer;
+use solana_transaction::Transaction;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::process::{Command, Output};
+
+const BLOCKHASH: &str = "4uQeVj5tqViQh7yWWGStvkEG1Zmhx6uasJtWCJziofM";
+
+fn fee_router(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_fee-router"))
+        .args(args)
+        .env_remove("FEE_ROUTER_URL")
+        .env_remove("FEE_ROUTER_KEYPAIR")
+        .output()
+        .unwrap()
+}
+
+/// Serve `getLatestBlockhash` on a local port until the test exits
+fn stub_rpc() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let mut stream = stream.unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut content_length = 0;
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if let Some(value) = line.to_ascii_lowercase().strip_prefix("content-length:") {
+                    content_length = value.trim().parse().unwrap();
+                }
+                if line == "\r\n" {
+                    break;
+                }
+            }
+            let mut request = vec![0; content_length];
+            reader.read_exact(&mut request).unwrap();
+            assert!(String::from_utf8(request).unwrap().contains("getLatestBlockhash"));
+
+            let body = serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "result": { "context": { "slot": 1 }, "value": { "blockhash": BLOCKHASH, "lastValidBlockHeight": 100 } },
+            })
+            .to_string();
+            write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            )
+            .unwrap();
+        }
+    });
+
+    url
+}
+
+fn keypair_file(keypair: &Keypair) -> std::path::PathBuf {
+    let path = std::env::temp_dir().join(format!("fee-router-{}.json", keypair.pubkey()));
+    std::fs::write(&path, serde_json::to_string(&keypair.to_bytes().to_vec()).unwrap()).unwrap();
+    path
+}
+
+#[test]
+fn help_lists_every_subcommand() {
+    let output = fee_router(&["--help"]);
+    assert!(output.status.success());
+
+    let help = String::from_utf8(output.stdout).unwrap();
+    for command in ["init", "collect", "distribute", "show", "set-authority", "list"] {
+        assert!(help.contains(command), "missing {command}");
+    }
+}
+
+#[test]
+fn set_authority_needs_a_new_authority_or_accept() {
+    let vault = Pubkey::new_unique().to_string();
+    assert_eq!(fee_router(&["set-authority", &vault]).status.code(), Some(2));
+
+    let other = Pubkey::new_unique().to_string();
+    assert_eq!(fee_router(&["set-authority", &vault, &other, "--accept"]).status.code(), Some(2));
+}
+
+#[test]
+fn reports_rpc_errors_as_json() {
+    let vault = Pubkey::new_unique().to_string();
+    let output = fee_router(&["show", &vault, "--url", "http://127.0.0.1:9", "--output", "json"]);
+    assert_eq!(output.status.code(), Some(1));
+
+    let error: serde_json::Value = serde_json::from_slice(&output.stderr).unwrap();
+    assert!(error["error"].as_str().unwrap().contains("RPC transport error"));
+}
+
+#[test]
+fn dry_run_prints_the_signed_transaction() {
+    let keypair = Keypair::new();
+    let keypair_path = keypair_file(&keypair);
+    let vault = Pubkey::new_unique();
+    let new_authority = Pubkey::new_unique();
+
+    let output = fee_router(&[
+        "set-authority",
+        &vault.to_string(),
+        &new_authority.to_string(),
+        "--dry-run",
+        "--output",
+        "json",
+        "--url",
+        &stub_rpc(),
+        "--keypair",
+        keypair_path.to_str().unwrap(),
+    ]);
+    std::fs::remove_file(&keypair_path).unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    let printed: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(printed["pending_authority"], new_authority.to_string());
+    assert!(printed.get("signature").is_none());
+
+    let bytes = base64::engine::general_purpose::STANDARD
+        .decode(printed["transaction"].as_str().unwrap())
+        .unwrap();
+    let transaction: Transaction = bincode::deserialize(&bytes).unwrap();
+    assert_eq!(transaction.signatures[0], keypair.sign_message(&transaction.message_data()));
+    assert_eq!(transaction.message.recent_blockhash.to_string(), BLOCKHASH);
+    assert_eq!(transaction.message.account_keys[0], keypair.pubkey());
+
+    let ix = &transaction.message.instructions[0];
+    assert_eq!(transaction.message.account_keys[ix.program_id_index as usize], meteora_fee_routing::ID);
+    assert_eq!(&ix.data[..8], instruction::ProposeAuthority::DISCRIMINATOR);
+    assert_eq!(ix.data[8..], new_authority.to_bytes());
+}
@@ -0,0 +1,25 @@
+use anchor_lang::prelude::Pubkey;
+
+#[derive(Debug, thiserror::Error)]
+pub enum ClientError {
+    #[error("account {0} not found")]
+    AccountNotFound(Pubkey),
+    #[error("account {address} is owned by {owner}, expected {expected}")]
+    InvalidOwner {
+        address: Pubkey,
+        owner: Pubkey,
+        expected: Pubkey,
+    },
+    #[error("failed to decode account: {0}")]
+    Decode(#[from] anchor_lang::error::Error),
+    #[error("RPC transport error: {0}")]
+    Transport(String),
+    #[error("RPC error {code}: {message}")]
+    Rpc { code: i64, message: String },
+    #[error("unexpected RPC response: {0}")]
+    InvalidResponse(String),
+    #[error("too many {what}: {count}, at most {max} fit in one instruction")]
+    TooManyAccounts { what: &'static str, count: usize, max: usize },
+}
+
+pub type Result<T> = std::result::Result<T, ClientError>;
@@ -0,0 +1,361 @@
+//! Instruction builders, one per program instruction.
+//!
+//! Each builder takes the program's generated accounts struct from
+//! `meteora_fee_routing::accounts` and the instruction arguments, and appends
+//! the instruction's remaining accounts in the order the program reads them.
+//! Instructions checked by `MultisigApproval` take `approvals`, the multisig
+//! members that sign the transaction. Leave it empty when the authority is a
+//! plain signer.
+
+use crate::error::{ClientError, Result};
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::InstructionData;
+use meteora_fee_routing::{accounts, instruction, ConfigChange, ProgramConfigParams, RecipientWeight, Role};
+
+/// One position of a `collect_many_position_fees` batch
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PositionAccounts {
+    pub position: Pubkey,
+    /// Every bin array the position spans, from `pda::dlmm_bin_arrays`
+    pub bin_arrays: Vec<Pubkey>,
+}
+
+/// Accounts for one initialized reward slot of `collect_position_rewards`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RewardAccounts {
+    pub reward_vault: Pubkey,
+    pub reward_mint: Pubkey,
+    /// The fee vault's ATA for `reward_mint` under `reward_token_program`
+    pub vault_reward_account: Pubkey,
+    pub reward_token_program: Pubkey,
+}
+
+/// A recipient's base and quote token accounts for `distribute_to_recipients`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RecipientTokenAccounts {
+    pub base: Pubkey,
+    pub quote: Pubkey,
+}
+
+fn build(
+    accounts: impl ToAccountMetas,
+    data: impl InstructionData,
+    remaining_accounts: impl IntoIterator<Item = AccountMeta>,
+) -> Instruction {
+    let mut metas = accounts.to_account_metas(None);
+    metas.extend(remaining_accounts);
+
+    Instruction {
+        program_id: meteora_fee_routing::ID,
+        accounts: metas,
+        data: data.data(),
+    }
+}
+
+fn signers(approvals: &[Pubkey]) -> impl Iterator<Item = AccountMeta> + '_ {
+    approvals.iter().map(|member| AccountMeta::new_readonly(*member, true))
+}
+
+fn writable(accounts: &[Pubkey]) -> impl Iterator<Item = AccountMeta> + '_ {
+    accounts.iter().map(|account| AccountMeta::new(*account, false))
+}
+
+/// A remaining account count that the program reads as a `u8` argument
+fn count(what: &'static str, count: usize) -> Result<u8> {
+    u8::try_from(count).map_err(|_| ClientError::TooManyAccounts { what, count, max: u8::MAX as usize })
+}
+
+pub fn initialize_program_config(
+    accounts: accounts::InitializeProgramConfig,
+    params: ProgramConfigParams,
+) -> Instruction {
+    build(accounts, instruction::InitializeProgramConfig { params }, [])
+}
+
+pub fn update_program_config(
+    accounts: accounts::UpdateProgramConfig,
+    params: ProgramConfigParams,
+) -> Instruction {
+    build(accounts, instruction::UpdateProgramConfig { params }, [])
+}
+
+/// `bump` is the fee vault's bump from `pda::fee_vault`
+pub fn initialize_fee_vault(accounts: accounts::InitializeFeeVault, bump: u8) -> Instruction {
+    build(accounts, instruction::InitializeFeeVault { bump }, [])
+}
+
+/// `accounts.position` is a new keypair, which signs along with the payer.
+/// `accounts.vault_position` is `pda::vault_position(fee_vault, position)`.
+pub fn open_vault_position(
+    accounts: accounts::OpenVaultPosition,
+    lower_bin_id: i32,
+    width: i32,
+    approvals: &[Pubkey],
+) -> Instruction {
+    build(accounts, instruction::OpenVaultPosition { lower_bin_id, width }, signers(approvals))
+}
+
+/// `accounts.vault_position` is `pda::vault_position(fee_vault, position)`
+pub fn register_vault_position(accounts: accounts::RegisterVaultPosition) -> Instruction {
+    build(accounts, instruction::RegisterVaultPosition {}, [])
+}
+
+/// The position must be closed on DLMM, or hold no liquidity
+pub fn deregister_vault_position(accounts: accounts::DeregisterVaultPosition, approvals: &[Pubkey]) -> Instruction {
+    build(accounts, instruction::DeregisterVaultPosition {}, signers(approvals))
+}
+
+/// `bin_arrays` are every bin array the position spans, from `pda::dlmm_bin_arrays`.
+/// Fails with `TooManyAccounts` for more than 255 bin arrays.
+pub fn collect_position_fees(
+    accounts: accounts::CollectPositionFees,
+    bin_arrays: &[Pubkey],
+    hook_accounts: &[AccountMeta],
+) -> Result<Instruction> {
+    Ok(build(
+        accounts,
+        instruction::CollectPositionFees {
+            bin_array_count: count("bin arrays", bin_arrays.len())?,
+        },
+        writable(bin_arrays).chain(hook_accounts.iter().cloned()),
+    ))
+}
+
+/// Collect from up to `MAX_POSITIONS_PER_BATCH` positions of the same pool.
+/// Fails with `TooManyAccounts` for a position with more than 255 bin arrays.
+pub fn collect_many_position_fees(
+    accounts: accounts::CollectManyPositionFees,
+    positions: &[PositionAccounts],
+    hook_accounts: &[AccountMeta],
+) -> Result<Instruction> {
+    let bin_array_counts = positions
+        .iter()
+        .map(|position| count("bin arrays", position.bin_arrays.len()))
+        .collect::<Result<Vec<u8>>>()?;
+    let fee_vault = accounts.fee_vault;
+    let position_metas = positions.iter().flat_map(|position| {
+        [
+            AccountMeta::new(position.position, false),
+            AccountMeta::new_readonly(crate::pda::vault_position(&fee_vault, &position.position).0, false),
+        ]
+        .into_iter()
+        .chain(writable(&position.bin_arrays))
+    });
+
+    Ok(build(
+        accounts,
+        instruction::CollectManyPositionFees { bin_array_counts },
+        position_metas.chain(hook_accounts.iter().cloned()),
+    ))
+}
+
+pub fn collect_protocol_fees(
+    accounts: accounts::CollectProtocolFees,
+    hook_accounts: &[AccountMeta],
+) -> Instruction {
+    build(accounts, instruction::CollectProtocolFees {}, hook_accounts.iter().cloned())
+}
+
+/// `bin_arrays` are every bin array the position spans, from `pda::dlmm_bin_arrays`.
+/// `rewards` holds one entry per initialized reward slot of the `LbPair`, in slot order.
+/// Fails with `TooManyAccounts` for more than 255 bin arrays.
+pub fn collect_position_rewards(
+    accounts: accounts::CollectPositionRewards,
+    bin_arrays: &[Pubkey],
+    rewards: &[RewardAccounts],
+    hook_accounts: &[AccountMeta],
+) -> Result<Instruction> {
+    let reward_metas = rewards.iter().flat_map(|reward| {
+        [
+            AccountMeta::new(reward.reward_vault, false),
+            AccountMeta::new_readonly(reward.reward_mint, false),
+            AccountMeta::new(reward.vault_reward_account, false),
+            AccountMeta::new_readonly(reward.reward_token_program, false),
+        ]
+    });
+
+    Ok(build(
+        accounts,
+        instruction::CollectPositionRewards {
+            bin_array_count: count("bin arrays", bin_arrays.len())?,
+        },
+        writable(bin_arrays).chain(reward_metas).chain(hook_accounts.iter().cloned()),
+    ))
+}
+
+/// `base_amount` and `quote_amount` are what the recipient receives
+pub fn distribute_fees(
+    accounts: accounts::DistributeFees,
+    base_amount: u64,
+    quote_amount: u64,
+    hook_accounts: &[AccountMeta],
+    approvals: &[Pubkey],
+) -> Instruction {
+    build(
+        accounts,
+        instruction::DistributeFees { base_amount, quote_amount },
+        hook_accounts.iter().cloned().chain(signers(approvals)),
+    )
+}
+
+pub fn initialize_distribution_config(
+    accounts: accounts::InitializeDistributionConfig,
+    recipients: Vec<RecipientWeight>,
+    approvals: &[Pubkey],
+) -> Instruction {
+    build(accounts, instruction::InitializeDistributionConfig { recipients }, signers(approvals))
+}
+
+pub fn update_distribution_config(
+    accounts: accounts::UpdateDistributionConfig,
+    recipients: Vec<RecipientWeight>,
+    approvals: &[Pubkey],
+) -> Instruction {
+    build(accounts, instruction::UpdateDistributionConfig { recipients }, signers(approvals))
+}
+
+/// `recipients` must follow the order of the distribution config
+pub fn distribute_to_recipients(
+    accounts: accounts::DistributeToRecipients,
+    recipients: &[RecipientTokenAccounts],
+    hook_accounts: &[AccountMeta],
+) -> Instruction {
+    let recipient_metas = recipients.iter().flat_map(|recipient| {
+        [
+            AccountMeta::new(recipient.base, false),
+            AccountMeta::new(recipient.quote, false),
+        ]
+    });
+
+    build(
+        accounts,
+        instruction::DistributeToRecipients {},
+        recipient_metas.chain(hook_accounts.iter().cloned()),
+    )
+}
+
+/// `recipient_accounts` holds each recipient's reward token account, in config order
+pub fn distribute_rewards_to_recipients(
+    accounts: accounts::DistributeRewardsToRecipients,
+    reward_index: u8,
+    recipient_accounts: &[Pubkey],
+    hook_accounts: &[AccountMeta],
+) -> Instruction {
+    build(
+        accounts,
+        instruction::DistributeRewardsToRecipients { reward_index },
+        writable(recipient_accounts).chain(hook_accounts.iter().cloned()),
+    )
+}
+
+pub fn open_recipient_position(
+    accounts: accounts::OpenRecipientPosition,
+    shares: u64,
+    approvals: &[Pubkey],
+) -> Instruction {
+    build(accounts, instruction::OpenRecipientPosition { shares }, signers(approvals))
+}
+
+pub fn update_recipient_shares(
+    accounts: accounts::UpdateRecipientShares,
+    shares: u64,
+    approvals: &[Pubkey],
+) -> Instruction {
+    build(accounts, instruction::UpdateRecipientShares { shares }, signers(approvals))
+}
+
+/// Zeroes the recipient's shares first, so any pending balance must be claimed
+pub fn close_recipient_position(accounts: accounts::CloseRecipientPosition, approvals: &[Pubkey]) -> Instruction {
+    build(accounts, instruction::CloseRecipientPosition {}, signers(approvals))
+}
+
+pub fn claim(accounts: accounts::Claim, hook_accounts: &[AccountMeta]) -> Instruction {
+    build(accounts, instruction::Claim {}, hook_accounts.iter().cloned())
+}
+
+pub fn propose_authority(
+    accounts: accounts::UpdateFeeVault,
+    new_authority: Pubkey,
+    approvals: &[Pubkey],
+) -> Instruction {
+    build(accounts, instruction::ProposeAuthority { new_authority }, signers(approvals))
+}
+
+pub fn accept_authority(accounts: accounts::AcceptAuthority, approvals: &[Pubkey]) -> Instruction {
+    build(accounts, instruction::AcceptAuthority {}, signers(approvals))
+}
+
+pub fn cancel_authority_transfer(accounts: accounts::UpdateFeeVault, approvals: &[Pubkey]) -> Instruction {
+    build(accounts, instruction::CancelAuthorityTransfer {}, signers(approvals))
+}
+
+pub fn set_pause_flags(accounts: accounts::SetPauseFlags, flags: u8, approvals: &[Pubkey]) -> Instruction {
+    build(accounts, instruction::SetPauseFlags { flags }, signers(approvals))
+}
+
+pub fn grant_role(
+    accounts: accounts::UpdateFeeVault,
+    role: Role,
+    holder: Pubkey,
+    approvals: &[Pubkey],
+) -> Instruction {
+    build(accounts, instruction::GrantRole { role, holder }, signers(approvals))
+}
+
+pub fn revoke_role(accounts: accounts::UpdateFeeVault, role: Role, approvals: &[Pubkey]) -> Instruction {
+    build(accounts, instruction::RevokeRole { role }, signers(approvals))
+}
+
+pub fn create_vault_multisig(
+    accounts: accounts::CreateVaultMultisig,
+    members: Vec<Pubkey>,
+    threshold: u8,
+    approvals: &[Pubkey],
+) -> Instruction {
+    build(accounts, instruction::CreateVaultMultisig { members, threshold }, signers(approvals))
+}
+
+/// `approvals` are members of the current signer set
+pub fn update_vault_multisig(
+    accounts: accounts::UpdateVaultMultisig,
+    members: Vec<Pubkey>,
+    threshold: u8,
+    approvals: &[Pubkey],
+) -> Instruction {
+    build(accounts, instruction::UpdateVaultMultisig { members, threshold }, signers(approvals))
+}
+
+pub fn set_config_delay(accounts: accounts::UpdateFeeVault, delay: i64, approvals: &[Pubkey]) -> Instruction {
+    build(accounts, instruction::SetConfigDelay { delay }, signers(approvals))
+}
+
+/// `accounts.queued_change` is `pda::config_change(fee_vault, fee_vault.next_change_id)`
+pub fn queue_config_change(
+    accounts: accounts::QueueConfigChange,
+    change: ConfigChange,
+    approvals: &[Pubkey],
+) -> Instruction {
+    build(accounts, instruction::QueueConfigChange { change }, signers(approvals))
+}
+
+pub fn execute_config_change(accounts: accounts::ExecuteConfigChange) -> Instruction {
+    build(accounts, instruction::ExecuteConfigChange {}, [])
+}
+
+pub fn cancel_config_change(accounts: accounts::CancelConfigChange, approvals: &[Pubkey]) -> Instruction {
+    build(accounts, instruction::CancelConfigChange {}, signers(approvals))
+}
+
+/// `accounts.fee_vault` is `pda::legacy_fee_vault(dlmm_pool)`, where the
+/// vault stays once migrated. The v0 vault's authority signs.
+pub fn migrate_fee_vault(accounts: accounts::MigrateFeeVault) -> Instruction {
+    build(accounts, instruction::MigrateFeeVault {}, [])
+}
+
+/// `accounts.vault_registry` is the registry page listing the vault, from
+/// `state::registry_page`. Every position must be deregistered, every
+/// recipient position closed and every queued change executed or cancelled.
+pub fn close_fee_vault(accounts: accounts::CloseFeeVault, approvals: &[Pubkey]) -> Instruction {
+    build(accounts, instruction::CloseFeeVault {}, signers(approvals))
+}
@@ -0,0 +1,20 @@
+//! Rust client for the Meteora fee routing program.
+//!
+//! - [`pda`] derives the program's addresses
+//! - [`instructions`] builds every instruction, remaining accounts included
+//! - [`state`] fetches and decodes `FeeVault`, the other program accounts and
+//!   the DLMM `LbPair` and position layouts
+//! - [`rpc`] (default `rpc` feature) is a small blocking JSON-RPC client
+//!
+//! Everything is synchronous, so the crate can be used from scripts, cranks
+//! and tests without an async runtime.
+
+pub mod error;
+pub mod instructions;
+pub mod pda;
+#[cfg(feature = "rpc")]
+pub mod rpc;
+pub mod state;
+
+pub use error::ClientError;
+pub use meteora_fee_routing::{self as program, accounts, ID as PROGRAM_ID};
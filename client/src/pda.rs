@@ -0,0 +1,104 @@
+//! Program derived addresses of the fee routing program, matching the `seeds`
+//! constraints of its accounts structs
+
+use anchor_lang::prelude::*;
+use anchor_spl::associated_token::get_associated_token_address_with_program_id;
+use meteora_fee_routing::dlmm_integration::meteora_dlmm_types::{self, DLMM_PROGRAM_ID};
+use meteora_fee_routing::ID;
+
+/// Bins per DLMM bin array
+pub const DLMM_BINS_PER_ARRAY: i64 = meteora_dlmm_types::MAX_BIN_PER_ARRAY;
+
+/// `[b"fee_vault", dlmm_pool, creator]`. The creator is the authority that
+/// initialized the vault, which stays fixed when the authority changes.
+pub fn fee_vault(dlmm_pool: &Pubkey, creator: &Pubkey) -> (Pubkey, u8) {
+    meteora_fee_routing::FeeVault::find_address(dlmm_pool, creator)
+}
+
+/// `[b"fee_vault", dlmm_pool]`, where the first release put a pool's only vault.
+/// It stays there once migrated, with `FeeVault.creator` unset.
+pub fn legacy_fee_vault(dlmm_pool: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"fee_vault", dlmm_pool.as_ref()], &ID)
+}
+
+/// `[b"program_config"]`
+pub fn program_config() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"program_config"], &ID)
+}
+
+/// `[b"vault_registry", page]`, with the page index little-endian
+pub fn vault_registry(page: u32) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"vault_registry", &page.to_le_bytes()], &ID)
+}
+
+/// `[b"distribution_config", fee_vault]`
+pub fn distribution_config(fee_vault: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"distribution_config", fee_vault.as_ref()], &ID)
+}
+
+/// `[b"recipient_position", fee_vault, recipient]`
+pub fn recipient_position(fee_vault: &Pubkey, recipient: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[b"recipient_position", fee_vault.as_ref(), recipient.as_ref()],
+        &ID,
+    )
+}
+
+/// `[b"vault_position", fee_vault, position]`
+pub fn vault_position(fee_vault: &Pubkey, position: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"vault_position", fee_vault.as_ref(), position.as_ref()], &ID)
+}
+
+/// `[b"vault_multisig", fee_vault]`
+pub fn vault_multisig(fee_vault: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"vault_multisig", fee_vault.as_ref()], &ID)
+}
+
+/// `[b"config_change", fee_vault, id]`, with the change id little-endian.
+/// The next change queued on a vault takes `FeeVault.next_change_id`.
+pub fn config_change(fee_vault: &Pubkey, id: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[b"config_change", fee_vault.as_ref(), &id.to_le_bytes()],
+        &ID,
+    )
+}
+
+/// The program's `ProgramData` account under the upgradeable loader, whose
+/// upgrade authority may initialize the program config
+pub fn program_data() -> Pubkey {
+    Pubkey::find_program_address(&[ID.as_ref()], &ProgramData::owner()).0
+}
+
+/// The fee vault's ATA for `mint`, owned by `token_program`
+pub fn vault_token_account(fee_vault: &Pubkey, mint: &Pubkey, token_program: &Pubkey) -> Pubkey {
+    get_associated_token_address_with_program_id(fee_vault, mint, token_program)
+}
+
+/// DLMM's `#[event_cpi]` authority
+pub fn dlmm_event_authority() -> Pubkey {
+    Pubkey::find_program_address(&[b"__event_authority"], &DLMM_PROGRAM_ID).0
+}
+
+/// DLMM `ClaimFeeOperator` at `[b"cf_operator", operator]`, which lets the
+/// fee vault `operator` claim protocol fees
+pub fn dlmm_claim_fee_operator(operator: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[meteora_dlmm_types::CLAIM_FEE_OPERATOR_SEED, operator.as_ref()], &DLMM_PROGRAM_ID).0
+}
+
+/// DLMM bin array at `[b"bin_array", lb_pair, index]`, with the index as an `i64` little-endian
+pub fn dlmm_bin_array(lb_pair: &Pubkey, index: i64) -> Pubkey {
+    Pubkey::find_program_address(&[b"bin_array", lb_pair.as_ref(), &index.to_le_bytes()], &DLMM_PROGRAM_ID).0
+}
+
+/// Index of the DLMM bin array holding `bin_id`, each array covering `DLMM_BINS_PER_ARRAY` bins
+pub fn dlmm_bin_array_index(bin_id: i32) -> i64 {
+    meteora_dlmm_types::bin_array_index(bin_id)
+}
+
+/// Every DLMM bin array covering `lower_bin_id..=upper_bin_id`, lowest first, the
+/// way the collect instructions take them
+pub fn dlmm_bin_arrays(lb_pair: &Pubkey, lower_bin_id: i32, upper_bin_id: i32) -> Vec<Pubkey> {
+    (dlmm_bin_array_index(lower_bin_id)..=dlmm_bin_array_index(upper_bin_id))
+        .map(|index| dlmm_bin_array(lb_pair, index))
+        .collect()
+}
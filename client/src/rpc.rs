@@ -0,0 +1,182 @@
+//! Minimal blocking JSON-RPC client, covering the calls needed to read vaults
+//! and send transactions without pulling in an async runtime
+
+use crate::error::{ClientError, Result};
+use crate::state::{AccountData, AccountSource};
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::Hash;
+use base64::Engine;
+use serde_json::{json, Value};
+use std::str::FromStr;
+
+/// Filter for `getProgramAccounts`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AccountFilter {
+    DataSize(u64),
+    Memcmp { offset: usize, bytes: Vec<u8> },
+}
+
+impl AccountFilter {
+    fn to_json(&self) -> Value {
+        match self {
+            Self::DataSize(size) => json!({ "dataSize": size }),
+            Self::Memcmp { offset, bytes } => json!({
+                "memcmp": {
+                    "offset": offset,
+                    "bytes": base64::engine::general_purpose::STANDARD.encode(bytes),
+                    "encoding": "base64",
+                }
+            }),
+        }
+    }
+}
+
+pub struct RpcClient {
+    url: String,
+    commitment: String,
+    agent: ureq::Agent,
+}
+
+impl RpcClient {
+    /// Client for `url` at `confirmed` commitment
+    pub fn new(url: impl Into<String>) -> Self {
+        Self::new_with_commitment(url, "confirmed")
+    }
+
+    pub fn new_with_commitment(url: impl Into<String>, commitment: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            commitment: commitment.into(),
+            agent: ureq::Agent::new(),
+        }
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    fn call(&self, method: &str, params: Value) -> Result<Value> {
+        let response: Value = self
+            .agent
+            .post(&self.url)
+            .send_json(json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params }))
+            .map_err(|error| ClientError::Transport(error.to_string()))?
+            .into_json()
+            .map_err(|error| ClientError::Transport(error.to_string()))?;
+
+        if let Some(error) = response.get("error") {
+            return Err(ClientError::Rpc {
+                code: error["code"].as_i64().unwrap_or_default(),
+                message: error["message"].as_str().unwrap_or_default().to_string(),
+            });
+        }
+        response
+            .get("result")
+            .cloned()
+            .ok_or_else(|| ClientError::InvalidResponse(format!("{method} returned no result")))
+    }
+
+    pub fn get_program_accounts(
+        &self,
+        program_id: &Pubkey,
+        filters: &[AccountFilter],
+    ) -> Result<Vec<(Pubkey, AccountData)>> {
+        let filters: Vec<Value> = filters.iter().map(AccountFilter::to_json).collect();
+        let result = self.call(
+            "getProgramAccounts",
+            json!([
+                program_id.to_string(),
+                { "encoding": "base64", "commitment": self.commitment, "filters": filters }
+            ]),
+        )?;
+
+        result
+            .as_array()
+            .ok_or_else(|| ClientError::InvalidResponse("getProgramAccounts result is not a list".into()))?
+            .iter()
+            .map(|entry| Ok((parse_pubkey(&entry["pubkey"])?, parse_account(&entry["account"])?)))
+            .collect()
+    }
+
+    pub fn get_latest_blockhash(&self) -> Result<Hash> {
+        let result = self.call("getLatestBlockhash", json!([{ "commitment": self.commitment }]))?;
+        let blockhash = result["value"]["blockhash"]
+            .as_str()
+            .ok_or_else(|| ClientError::InvalidResponse("missing blockhash".into()))?;
+
+        Hash::from_str(blockhash).map_err(|error| ClientError::InvalidResponse(error.to_string()))
+    }
+
+    pub fn get_minimum_balance_for_rent_exemption(&self, data_len: usize) -> Result<u64> {
+        self.call("getMinimumBalanceForRentExemption", json!([data_len]))?
+            .as_u64()
+            .ok_or_else(|| ClientError::InvalidResponse("rent is not a number".into()))
+    }
+
+    /// Send a signed transaction in wire format and return its signature
+    pub fn send_transaction(&self, transaction: &[u8]) -> Result<String> {
+        let result = self.call(
+            "sendTransaction",
+            json!([
+                base64::engine::general_purpose::STANDARD.encode(transaction),
+                { "encoding": "base64", "preflightCommitment": self.commitment }
+            ]),
+        )?;
+
+        result
+            .as_str()
+            .map(str::to_string)
+            .ok_or_else(|| ClientError::InvalidResponse("signature is not a string".into()))
+    }
+
+    /// Whether `signature` has reached the client's commitment, and its error if it failed
+    pub fn get_signature_status(&self, signature: &str) -> Result<Option<std::result::Result<(), String>>> {
+        let result = self.call("getSignatureStatuses", json!([[signature]]))?;
+        let status = &result["value"][0];
+        if status.is_null() || status["confirmationStatus"].as_str() == Some("processed") {
+            return Ok(None);
+        }
+
+        Ok(Some(match &status["err"] {
+            Value::Null => Ok(()),
+            error => Err(error.to_string()),
+        }))
+    }
+}
+
+impl AccountSource for RpcClient {
+    fn get_account(&self, address: &Pubkey) -> Result<Option<AccountData>> {
+        let result = self.call(
+            "getAccountInfo",
+            json!([address.to_string(), { "encoding": "base64", "commitment": self.commitment }]),
+        )?;
+
+        match &result["value"] {
+            Value::Null => Ok(None),
+            account => parse_account(account).map(Some),
+        }
+    }
+}
+
+fn parse_pubkey(value: &Value) -> Result<Pubkey> {
+    value
+        .as_str()
+        .and_then(|key| Pubkey::from_str(key).ok())
+        .ok_or_else(|| ClientError::InvalidResponse(format!("invalid pubkey {value}")))
+}
+
+fn parse_account(value: &Value) -> Result<AccountData> {
+    let data = value["data"][0]
+        .as_str()
+        .ok_or_else(|| ClientError::InvalidResponse("account data is not base64".into()))?;
+
+    Ok(AccountData {
+        owner: parse_pubkey(&value["owner"])?,
+        lamports: value["lamports"]
+            .as_u64()
+            .ok_or_else(|| ClientError::InvalidResponse("missing lamports".into()))?,
+        data: base64::engine::general_purpose::STANDARD
+            .decode(data)
+            .map_err(|error| ClientError::InvalidResponse(error.to_string()))?,
+    })
+}
@@ -0,0 +1,124 @@
+//! Fetching and decoding of fee routing and DLMM accounts
+
+use crate::error::{ClientError, Result};
+use anchor_lang::prelude::*;
+use meteora_fee_routing::dlmm_integration::meteora_dlmm_types::{
+    load_from_bytes, DlmmPosition, LbPair, DLMM_PROGRAM_ID,
+};
+use meteora_fee_routing::{FeeVault, FeeVaultV0, ProgramConfig, VaultRegistry};
+use std::collections::HashMap;
+
+/// An account as stored on chain
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AccountData {
+    pub owner: Pubkey,
+    pub lamports: u64,
+    pub data: Vec<u8>,
+}
+
+/// Somewhere to read accounts from, such as an RPC node or a map of
+/// fixtures. Implementations return `Ok(None)` for accounts that don't exist.
+pub trait AccountSource {
+    fn get_account(&self, address: &Pubkey) -> Result<Option<AccountData>>;
+}
+
+impl AccountSource for HashMap<Pubkey, AccountData> {
+    fn get_account(&self, address: &Pubkey) -> Result<Option<AccountData>> {
+        Ok(self.get(address).cloned())
+    }
+}
+
+/// Fetch an account that must exist and be owned by `owner`
+pub fn fetch_raw(source: &impl AccountSource, address: &Pubkey, owner: &Pubkey) -> Result<AccountData> {
+    let account = source
+        .get_account(address)?
+        .ok_or(ClientError::AccountNotFound(*address))?;
+    if account.owner != *owner {
+        return Err(ClientError::InvalidOwner {
+            address: *address,
+            owner: account.owner,
+            expected: *owner,
+        });
+    }
+
+    Ok(account)
+}
+
+/// Fetch and decode a fee routing program account, checking its owner and discriminator
+pub fn fetch<T: AccountDeserialize + Owner>(source: &impl AccountSource, address: &Pubkey) -> Result<T> {
+    let account = fetch_raw(source, address, &T::owner())?;
+    Ok(T::try_deserialize(&mut &account.data[..])?)
+}
+
+/// Whether raw fee vault data still has the v0 layout and needs `migrate_fee_vault`
+pub fn is_v0_fee_vault(data: &[u8]) -> bool {
+    data.len() == FeeVaultV0::LEN
+}
+
+/// Decode a fee vault in the current layout. v0 vaults fail to decode, use
+/// `decode_fee_vault_v0` for those.
+pub fn decode_fee_vault(data: &[u8]) -> Result<FeeVault> {
+    Ok(FeeVault::try_deserialize(&mut &data[..])?)
+}
+
+/// Decode a v0 fee vault, from `pda::legacy_fee_vault`
+pub fn decode_fee_vault_v0(data: &[u8]) -> Result<FeeVaultV0> {
+    Ok(FeeVaultV0::from_data(data)?)
+}
+
+pub fn fetch_fee_vault(source: &impl AccountSource, address: &Pubkey) -> Result<FeeVault> {
+    let account = fetch_raw(source, address, &meteora_fee_routing::ID)?;
+    decode_fee_vault(&account.data)
+}
+
+/// Decode a DLMM `LbPair` (discriminator included)
+pub fn decode_lb_pair(data: &[u8]) -> Result<LbPair> {
+    Ok(*load_from_bytes::<LbPair>(data)?)
+}
+
+pub fn fetch_lb_pair(source: &impl AccountSource, address: &Pubkey) -> Result<LbPair> {
+    let account = fetch_raw(source, address, &DLMM_PROGRAM_ID)?;
+    decode_lb_pair(&account.data)
+}
+
+/// Decode a DLMM position of either layout, borrowing from `data`
+pub fn decode_position(data: &[u8]) -> Result<DlmmPosition<'_>> {
+    Ok(DlmmPosition::from_bytes(data)?)
+}
+
+/// Fetch a DLMM position's account. Decode it with `decode_position`.
+pub fn fetch_position(source: &impl AccountSource, address: &Pubkey) -> Result<AccountData> {
+    fetch_raw(source, address, &DLMM_PROGRAM_ID)
+}
+
+/// Every fee vault in the registry, in registration order. Closing a vault
+/// takes it off its page.
+pub fn registered_fee_vaults(source: &impl AccountSource) -> Result<Vec<Pubkey>> {
+    let mut vaults = Vec::new();
+    for registry in registry_pages(source)? {
+        vaults.extend(registry.vaults);
+    }
+
+    Ok(vaults)
+}
+
+/// The registry page listing `fee_vault`, as `close_fee_vault` needs it, or
+/// `None` when no page lists it
+pub fn registry_page(source: &impl AccountSource, fee_vault: &Pubkey) -> Result<Option<u32>> {
+    Ok(registry_pages(source)?
+        .into_iter()
+        .find(|registry| registry.vaults.contains(fee_vault))
+        .map(|registry| registry.page))
+}
+
+fn registry_pages(source: &impl AccountSource) -> Result<Vec<VaultRegistry>> {
+    let program_config: ProgramConfig = fetch(source, &crate::pda::program_config().0)?;
+    if program_config.vault_count == 0 {
+        return Ok(Vec::new());
+    }
+
+    let last_page = ((program_config.vault_count - 1) / meteora_fee_routing::VAULTS_PER_PAGE) as u32;
+    (0..=last_page)
+        .map(|page| fetch(source, &crate::pda::vault_registry(page).0))
+        .collect()
+}
@@ -0,0 +1,291 @@
+//! Checks the client's addresses, instruction layouts and decoders against the
+//! program and the account dumps in `tests/fixtures`.
+
+use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
+use base64::Engine;
+use meteora_fee_routing::dlmm_integration::meteora_dlmm_types::{DLMM_EVENT_AUTHORITY, DLMM_PROGRAM_ID};
+use meteora_fee_routing::{instruction, FeeVault};
+use meteora_fee_routing_client::instructions::{self, PositionAccounts};
+use meteora_fee_routing_client::state::{self, AccountData};
+use meteora_fee_routing_client::{accounts, pda, ClientError};
+use std::collections::HashMap;
+use std::str::FromStr;
+
+fn load_fixture(name: &str) -> (Pubkey, AccountData) {
+    let path = format!("{}/../tests/fixtures/{}", env!("CARGO_MANIFEST_DIR"), name);
+    let json: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    let account = &json["account"];
+
+    let data = AccountData {
+        owner: Pubkey::from_str(account["owner"].as_str().unwrap()).unwrap(),
+        lamports: account["lamports"].as_u64().unwrap(),
+        data: base64::engine::general_purpose::STANDARD
+            .decode(account["data"][0].as_str().unwrap())
+            .unwrap(),
+    };
+    (Pubkey::from_str(json["pubkey"].as_str().unwrap()).unwrap(), data)
+}
+
+fn fixtures() -> HashMap<Pubkey, AccountData> {
+    ["lb_pair.json", "position.json", "position_v2.json", "fee_vault_v0.json", "collect_lb_pair.json"]
+        .into_iter()
+        .map(load_fixture)
+        .collect()
+}
+
+#[test]
+fn derives_the_program_addresses() {
+    let pool = Pubkey::new_unique();
+    let creator = Pubkey::new_unique();
+    let (fee_vault, bump) = pda::fee_vault(&pool, &creator);
+    assert_eq!(
+        (fee_vault, bump),
+        Pubkey::find_program_address(&[b"fee_vault", pool.as_ref(), creator.as_ref()], &meteora_fee_routing::ID)
+    );
+    assert_ne!(pda::fee_vault(&pool, &Pubkey::new_unique()).0, fee_vault);
+
+    assert_ne!(pda::vault_registry(0).0, pda::vault_registry(1).0);
+    assert_ne!(pda::config_change(&fee_vault, 0).0, pda::config_change(&fee_vault, 1).0);
+    assert_eq!(pda::dlmm_event_authority(), DLMM_EVENT_AUTHORITY);
+    assert_eq!(
+        pda::dlmm_claim_fee_operator(&fee_vault),
+        Pubkey::find_program_address(&[b"cf_operator", fee_vault.as_ref()], &DLMM_PROGRAM_ID).0
+    );
+
+    // Bin arrays cover 70 bins each, with negative bins rounding down
+    assert_eq!(pda::dlmm_bin_array_index(0), 0);
+    assert_eq!(pda::dlmm_bin_array_index(69), 0);
+    assert_eq!(pda::dlmm_bin_array_index(-1), -1);
+    assert_eq!(pda::dlmm_bin_array_index(-4355), -63);
+
+    // A position wider than 70 bins spans every array in between
+    let lb_pair = Pubkey::new_unique();
+    assert_eq!(
+        pda::dlmm_bin_arrays(&lb_pair, -71, 140),
+        [-2, -1, 0, 1, 2].map(|index| pda::dlmm_bin_array(&lb_pair, index))
+    );
+    assert_eq!(pda::dlmm_bin_arrays(&lb_pair, 5, 9), [pda::dlmm_bin_array(&lb_pair, 0)]);
+}
+
+#[test]
+fn collect_many_appends_positions_with_their_registrations_and_bin_arrays_then_hooks() {
+    let positions = [
+        PositionAccounts {
+            position: Pubkey::new_unique(),
+            bin_arrays: vec![Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique()],
+        },
+        PositionAccounts {
+            position: Pubkey::new_unique(),
+            bin_arrays: vec![Pubkey::new_unique()],
+        },
+    ];
+    let hook = AccountMeta::new_readonly(Pubkey::new_unique(), false);
+    let treasury_base_account = Pubkey::new_unique();
+    let fee_vault = Pubkey::new_unique();
+
+    let ix = instructions::collect_many_position_fees(
+        accounts::CollectManyPositionFees {
+            fee_vault,
+            lb_pair: Pubkey::new_unique(),
+            base_token_account: Pubkey::new_unique(),
+            quote_token_account: Pubkey::new_unique(),
+            reserve_x: Pubkey::new_unique(),
+            reserve_y: Pubkey::new_unique(),
+            base_mint: Pubkey::new_unique(),
+            quote_mint: Pubkey::new_unique(),
+            collector: Pubkey::new_unique(),
+            base_token_program: anchor_spl::token::ID,
+            quote_token_program: anchor_spl::token::ID,
+            memo_program: Pubkey::new_unique(),
+            event_authority: DLMM_EVENT_AUTHORITY,
+            dlmm_program: DLMM_PROGRAM_ID,
+            program_config: pda::program_config().0,
+            treasury_base_account: Some(treasury_base_account),
+            treasury_quote_account: None,
+        },
+        &positions,
+        std::slice::from_ref(&hook),
+    )
+    .unwrap();
+
+    assert_eq!(ix.program_id, meteora_fee_routing::ID);
+    assert_eq!(&ix.data[..8], instruction::CollectManyPositionFees::DISCRIMINATOR);
+    // `bin_array_counts` as a Borsh `Vec<u8>`
+    assert_eq!(ix.data[8..], [2, 0, 0, 0, 3, 1]);
+
+    // 17 named accounts, an omitted optional one standing in as the program id
+    assert_eq!(ix.accounts.len(), 17 + 8 + 1);
+    assert_eq!(ix.accounts[15].pubkey, treasury_base_account);
+    assert_eq!(ix.accounts[16].pubkey, meteora_fee_routing::ID);
+    assert_eq!(ix.accounts[17], AccountMeta::new(positions[0].position, false));
+    assert_eq!(
+        ix.accounts[18],
+        AccountMeta::new_readonly(pda::vault_position(&fee_vault, &positions[0].position).0, false)
+    );
+    assert_eq!(ix.accounts[21], AccountMeta::new(positions[0].bin_arrays[2], false));
+    assert_eq!(ix.accounts[22], AccountMeta::new(positions[1].position, false));
+    assert_eq!(
+        ix.accounts[23],
+        AccountMeta::new_readonly(pda::vault_position(&fee_vault, &positions[1].position).0, false)
+    );
+    assert_eq!(ix.accounts[24], AccountMeta::new(positions[1].bin_arrays[0], false));
+    assert_eq!(ix.accounts[25], hook);
+}
+
+#[test]
+fn close_fee_vault_passes_approvals_after_the_named_accounts() {
+    let fee_vault = Pubkey::new_unique();
+    let multisig = pda::vault_multisig(&fee_vault).0;
+    let members = [Pubkey::new_unique()];
+
+    let ix = instructions::close_fee_vault(
+        accounts::CloseFeeVault {
+            fee_vault,
+            authority: multisig,
+            base_token_account: Pubkey::new_unique(),
+            quote_token_account: Pubkey::new_unique(),
+            base_mint: Pubkey::new_unique(),
+            quote_mint: Pubkey::new_unique(),
+            distribution_config: pda::distribution_config(&fee_vault).0,
+            vault_multisig: multisig,
+            vault_registry: pda::vault_registry(0).0,
+            rent_receiver: Pubkey::new_unique(),
+            base_token_program: anchor_spl::token::ID,
+            quote_token_program: anchor_spl::token::ID,
+        },
+        &members,
+    );
+
+    assert_eq!(ix.data, instruction::CloseFeeVault::DISCRIMINATOR);
+    assert!(!ix.accounts[1].is_signer);
+    assert_eq!(ix.accounts.len(), 13);
+    assert_eq!(ix.accounts[12], AccountMeta::new_readonly(members[0], true));
+}
+
+#[test]
+fn open_vault_position_signs_with_the_new_position() {
+    let fee_vault = Pubkey::new_unique();
+    let position = Pubkey::new_unique();
+    let authority = Pubkey::new_unique();
+
+    let ix = instructions::open_vault_position(
+        accounts::OpenVaultPosition {
+            vault_position: pda::vault_position(&fee_vault, &position).0,
+            fee_vault,
+            authority,
+            lb_pair: Pubkey::new_unique(),
+            position,
+            event_authority: DLMM_EVENT_AUTHORITY,
+            dlmm_program: DLMM_PROGRAM_ID,
+            program_config: pda::program_config().0,
+            payer: authority,
+            system_program: anchor_lang::system_program::ID,
+            rent: anchor_lang::solana_program::sysvar::rent::ID,
+        },
+        -35,
+        70,
+        &[],
+    );
+
+    let mut data = instruction::OpenVaultPosition::DISCRIMINATOR.to_vec();
+    data.extend((-35i32).to_le_bytes());
+    data.extend(70i32.to_le_bytes());
+    assert_eq!(ix.data, data);
+    assert_eq!(ix.accounts.len(), 11);
+    assert_eq!(ix.accounts[4], AccountMeta::new(position, true));
+    assert_eq!(ix.accounts[8], AccountMeta::new(authority, true));
+}
+
+#[test]
+fn rejects_counts_that_overflow_the_instruction_argument() {
+    let fee_vault = Pubkey::new_unique();
+    let position = Pubkey::new_unique();
+    let lb_pair = Pubkey::new_unique();
+    let accounts = accounts::CollectPositionFees {
+        fee_vault,
+        lb_pair,
+        position,
+        vault_position: pda::vault_position(&fee_vault, &position).0,
+        base_token_account: Pubkey::new_unique(),
+        quote_token_account: Pubkey::new_unique(),
+        reserve_x: Pubkey::new_unique(),
+        reserve_y: Pubkey::new_unique(),
+        base_mint: Pubkey::new_unique(),
+        quote_mint: Pubkey::new_unique(),
+        collector: Pubkey::new_unique(),
+        base_token_program: anchor_spl::token::ID,
+        quote_token_program: anchor_spl::token::ID,
+        memo_program: Pubkey::new_unique(),
+        event_authority: DLMM_EVENT_AUTHORITY,
+        dlmm_program: DLMM_PROGRAM_ID,
+        program_config: pda::program_config().0,
+        treasury_base_account: None,
+        treasury_quote_account: None,
+    };
+    let bin_arrays = vec![Pubkey::new_unique(); 256];
+
+    assert!(matches!(
+        instructions::collect_position_fees(accounts, &bin_arrays, &[]),
+        Err(ClientError::TooManyAccounts { what: "bin arrays", count: 256, max: 255 })
+    ));
+}
+
+#[test]
+fn decodes_v0_fee_vault_at_its_legacy_address() {
+    let fixtures = fixtures();
+    let (address, raw) = load_fixture("fee_vault_v0.json");
+    assert!(state::is_v0_fee_vault(&raw.data));
+
+    // Only the migrated vault decodes as `FeeVault`
+    assert!(matches!(state::fetch_fee_vault(&fixtures, &address), Err(ClientError::Decode(_))));
+    let legacy = state::decode_fee_vault_v0(&raw.data).unwrap();
+    assert_eq!(pda::legacy_fee_vault(&legacy.dlmm_pool), (address, legacy.bump));
+    assert_eq!(legacy.total_collected_base, 5_000);
+
+    // Migrated in place, with the creator unset
+    let mut data = vec![0; FeeVault::LEN];
+    legacy.clone().into_current(Pubkey::default(), legacy.bump, 0).try_serialize(&mut &mut data[..]).unwrap();
+    assert!(!state::is_v0_fee_vault(&data));
+    assert_eq!(state::decode_fee_vault(&data).unwrap().creator, Pubkey::default());
+
+    let lb_pair = state::fetch_lb_pair(&fixtures, &legacy.dlmm_pool).unwrap();
+    assert_eq!(lb_pair.bin_step, 10);
+}
+
+#[test]
+fn decodes_both_position_layouts() {
+    let fixtures = fixtures();
+    let (lb_pair, _) = load_fixture("lb_pair.json");
+
+    for name in ["position.json", "position_v2.json"] {
+        let (address, _) = load_fixture(name);
+        let account = state::fetch_position(&fixtures, &address).unwrap();
+        let position = state::decode_position(&account.data).unwrap();
+
+        assert_eq!(position.lb_pair(), lb_pair);
+        assert_eq!(position.lower_bin_id(), -4355);
+        assert!(position.has_liquidity());
+    }
+}
+
+#[test]
+fn rejects_missing_and_foreign_accounts() {
+    let fixtures = fixtures();
+    let (lb_pair, _) = load_fixture("lb_pair.json");
+
+    let missing = Pubkey::new_unique();
+    assert!(matches!(
+        state::fetch_fee_vault(&fixtures, &missing),
+        Err(ClientError::AccountNotFound(address)) if address == missing
+    ));
+    assert!(matches!(
+        state::fetch_fee_vault(&fixtures, &lb_pair),
+        Err(ClientError::InvalidOwner { owner, .. }) if owner == DLMM_PROGRAM_ID
+    ));
+    assert!(matches!(
+        state::registered_fee_vaults(&fixtures),
+        Err(ClientError::AccountNotFound(_))
+    ));
+}
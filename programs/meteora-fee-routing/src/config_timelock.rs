@@ -0,0 +1,265 @@
+use anchor_lang::prelude::*;
+
+/// Longest delay a vault can put on its configuration changes (30 days)
+pub const MAX_CONFIG_DELAY: i64 = 30 * 24 * 60 * 60;
+
+/// A configuration change waiting out the vault's `config_delay`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq)]
+pub enum ConfigChange {
+    /// Propose a new authority, who still has to call `accept_authority`
+    Authority { new_authority: Pubkey },
+    /// Grant a delegated role, or revoke it with `None`
+    Role { role: Role, holder: Option<Pubkey> },
+    /// Replace the weighted split table
+    Recipients { recipients: Vec<RecipientWeight> },
+    /// Change the share count of an existing recipient position
+    RecipientShares { recipient: Pubkey, shares: u64 },
+    ConfigDelay { delay: i64 },
+    /// Replace the vault multisig's members and threshold
+    Multisig { members: Vec<Pubkey>, threshold: u8 },
+}
+
+impl ConfigChange {
+    /// Serialized size of the largest variant, `Recipients` with a full table.
+    /// `Multisig` with a full member list takes `4 + 32 * MAX_MULTISIG_MEMBERS + 1`.
+    pub const MAX_LEN: usize = 1 + // variant
+        4 + RecipientWeight::LEN * MAX_RECIPIENTS;
+
+    /// Role allowed to queue and cancel the change
+    pub fn required_role(&self) -> Role {
+        match self {
+            ConfigChange::Recipients { .. } | ConfigChange::RecipientShares { .. } => Role::ConfigManager,
+            _ => Role::Admin,
+        }
+    }
+}
+
+/// Queued, timelocked configuration changes
+pub struct ConfigTimelock;
+
+impl ConfigTimelock {
+    /// Reject a change up front rather than when it is executed
+    pub fn validate(change: &ConfigChange) -> Result<()> {
+        match change {
+            ConfigChange::Recipients { recipients } => WeightedDistribution::validate_recipients(recipients),
+            ConfigChange::ConfigDelay { delay } => Self::validate_delay(*delay),
+            ConfigChange::Multisig { members, threshold } => MultisigApproval::validate(members, *threshold),
+            ConfigChange::Role { role: Role::Admin, .. } => err!(FeeRoutingError::InvalidRole),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn validate_delay(delay: i64) -> Result<()> {
+        require!(
+            (0..=MAX_CONFIG_DELAY).contains(&delay),
+            FeeRoutingError::InvalidConfigDelay
+        );
+        Ok(())
+    }
+
+    /// Stop counting a change that was executed or cancelled
+    pub fn dequeue(fee_vault: &mut FeeVault) -> Result<()> {
+        fee_vault.queued_change_count = fee_vault.queued_change_count
+            .checked_sub(1)
+            .ok_or(FeeRoutingError::MathOverflow)?;
+        Ok(())
+    }
+
+    /// Apply a change whose delay has passed
+    pub fn apply(accounts: &mut ExecuteConfigChange, change: ConfigChange) -> Result<()> {
+        let slot = Clock::get()?.slot;
+        let fee_vault = &mut accounts.fee_vault;
+
+        match change {
+            ConfigChange::Authority { new_authority } => {
+                fee_vault.pending_authority = Some(new_authority);
+
+                emit!(AuthorityProposed {
+                    fee_vault: fee_vault.key(),
+                    dlmm_pool: fee_vault.dlmm_pool,
+                    authority: fee_vault.authority,
+                    pending_authority: new_authority,
+                    slot,
+                });
+            }
+            ConfigChange::Role { role, holder } => VaultRoles::assign(fee_vault, role, holder)?,
+            ConfigChange::Recipients { recipients } => {
+                let distribution_config = accounts.distribution_config
+                    .as_mut()
+                    .ok_or(FeeRoutingError::MissingConfigChangeAccount)?;
+                distribution_config.recipients = recipients;
+
+                emit!(DistributionConfigUpdated {
+                    fee_vault: fee_vault.key(),
+                    dlmm_pool: fee_vault.dlmm_pool,
+                    recipients: distribution_config.recipients.clone(),
+                    slot,
+                });
+            }
+            ConfigChange::RecipientShares { recipient, shares } => {
+                let recipient_position = accounts.recipient_position
+                    .as_mut()
+                    .ok_or(FeeRoutingError::MissingConfigChangeAccount)?;
+                require_keys_eq!(
+                    recipient_position.recipient,
+                    recipient,
+                    FeeRoutingError::MissingConfigChangeAccount
+                );
+                PullClaims::set_shares(fee_vault, recipient_position, shares)?;
+
+                emit!(RecipientSharesUpdated {
+                    fee_vault: fee_vault.key(),
+                    dlmm_pool: fee_vault.dlmm_pool,
+                    recipient,
+                    shares,
+                    total_shares: fee_vault.total_shares,
+                    slot,
+                });
+            }
+            ConfigChange::ConfigDelay { delay } => {
+                let old_delay = fee_vault.config_delay;
+                fee_vault.config_delay = delay;
+
+                emit!(ConfigDelayChanged {
+                    fee_vault: fee_vault.key(),
+                    dlmm_pool: fee_vault.dlmm_pool,
+                    old_delay,
+                    new_delay: delay,
+                    slot,
+                });
+            }
+            ConfigChange::Multisig { members, threshold } => {
+                let vault_multisig = accounts.vault_multisig
+                    .as_mut()
+                    .ok_or(FeeRoutingError::MissingConfigChangeAccount)?;
+                vault_multisig.threshold = threshold;
+                vault_multisig.members = members;
+
+                emit!(MultisigUpdated {
+                    fee_vault: fee_vault.key(),
+                    dlmm_pool: fee_vault.dlmm_pool,
+                    multisig: vault_multisig.key(),
+                    members: vault_multisig.members.clone(),
+                    threshold,
+                    slot,
+                });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+#[instruction(change: ConfigChange)]
+pub struct QueueConfigChange<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = QueuedConfigChange::LEN,
+        seeds = [b"config_change", fee_vault.key().as_ref(), &fee_vault.next_change_id.to_le_bytes()],
+        bump
+    )]
+    pub queued_change: Account<'info, QueuedConfigChange>,
+
+    #[account(
+        mut,
+        seeds = [b"fee_vault", fee_vault.dlmm_pool.as_ref(), fee_vault.creator_seed()],
+        bump = fee_vault.bump,
+        constraint = fee_vault.has_role(change.required_role(), &authority.key()) @ FeeRoutingError::MissingRole
+    )]
+    pub fee_vault: Account<'info, FeeVault>,
+
+    /// CHECK: Holder of the change's required role, approved through `MultisigApproval::authorize`
+    pub authority: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteConfigChange<'info> {
+    #[account(
+        mut,
+        seeds = [b"config_change", fee_vault.key().as_ref(), &queued_change.id.to_le_bytes()],
+        bump = queued_change.bump,
+        has_one = fee_vault,
+        close = rent_receiver
+    )]
+    pub queued_change: Account<'info, QueuedConfigChange>,
+
+    #[account(
+        mut,
+        seeds = [b"fee_vault", fee_vault.dlmm_pool.as_ref(), fee_vault.creator_seed()],
+        bump = fee_vault.bump
+    )]
+    pub fee_vault: Account<'info, FeeVault>,
+
+    /// Required for `ConfigChange::Recipients`
+    #[account(
+        mut,
+        seeds = [b"distribution_config", fee_vault.key().as_ref()],
+        bump = distribution_config.bump,
+        has_one = fee_vault
+    )]
+    pub distribution_config: Option<Account<'info, DistributionConfig>>,
+
+    /// Required for `ConfigChange::RecipientShares`
+    #[account(
+        mut,
+        seeds = [b"recipient_position", fee_vault.key().as_ref(), recipient_position.recipient.as_ref()],
+        bump = recipient_position.bump,
+        has_one = fee_vault
+    )]
+    pub recipient_position: Option<Account<'info, RecipientPosition>>,
+
+    /// Required for `ConfigChange::Multisig`
+    #[account(
+        mut,
+        seeds = [b"vault_multisig", fee_vault.key().as_ref()],
+        bump = vault_multisig.bump,
+        has_one = fee_vault
+    )]
+    pub vault_multisig: Option<Account<'info, VaultMultisig>>,
+
+    /// CHECK: Receives the queued change's rent back, pinned to whoever paid it
+    #[account(mut, address = queued_change.payer @ FeeRoutingError::Unauthorized)]
+    pub rent_receiver: UncheckedAccount<'info>,
+
+    pub executor: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CancelConfigChange<'info> {
+    #[account(
+        mut,
+        seeds = [b"config_change", fee_vault.key().as_ref(), &queued_change.id.to_le_bytes()],
+        bump = queued_change.bump,
+        has_one = fee_vault,
+        close = rent_receiver
+    )]
+    pub queued_change: Account<'info, QueuedConfigChange>,
+
+    #[account(
+        mut,
+        seeds = [b"fee_vault", fee_vault.dlmm_pool.as_ref(), fee_vault.creator_seed()],
+        bump = fee_vault.bump,
+        constraint = fee_vault.has_role(queued_change.change.required_role(), &authority.key()) @ FeeRoutingError::MissingRole
+    )]
+    pub fee_vault: Account<'info, FeeVault>,
+
+    /// CHECK: Receives the queued change's rent back, pinned to whoever paid it
+    #[account(mut, address = queued_change.payer @ FeeRoutingError::Unauthorized)]
+    pub rent_receiver: UncheckedAccount<'info>,
+
+    /// CHECK: Holder of the change's required role, approved through `MultisigApproval::authorize`
+    pub authority: UncheckedAccount<'info>,
+}
+
+use crate::events::*;
+use crate::{
+    DistributionConfig, FeeVault, FeeRoutingError, MultisigApproval, PullClaims, QueuedConfigChange,
+    RecipientPosition, RecipientWeight, Role, VaultMultisig, VaultRoles, WeightedDistribution, MAX_RECIPIENTS,
+};
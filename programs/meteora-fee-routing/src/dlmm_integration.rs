@@ -2,11 +2,87 @@ use anchor_lang::prelude::*;
 
 pub mod meteora_dlmm_types {
     use super::*;
+    use anchor_lang::ZeroCopy;
+    use bytemuck::{Pod, Zeroable};
 
-    /// Meteora DLMM Program ID
+    /// Meteora DLMM Program ID. Collection CPIs go to whichever allowlisted
+    /// DLMM program is passed; this is the mainnet deployment.
     pub const DLMM_PROGRAM_ID: Pubkey = pubkey!("LBUZKhRxPF3XUpBCjp4YzTKgLccjZhTSDM9YuVaPwxo");
 
-    #[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+    /// SPL Memo program, passed to DLMM's Token-2022 aware instructions
+    pub const MEMO_PROGRAM_ID: Pubkey = pubkey!("MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr");
+
+    /// Seed of the DLMM `#[event_cpi]` authority PDA
+    pub const DLMM_EVENT_AUTHORITY_SEED: &[u8] = b"__event_authority";
+
+    /// DLMM `#[event_cpi]` authority, the PDA of `[b"__event_authority"]` under `DLMM_PROGRAM_ID`
+    pub const DLMM_EVENT_AUTHORITY: Pubkey = pubkey!("D1ZN9Wj1fRSUQfCjhvnu1hqDMT7hzjzBBpi12nVniYD6");
+
+    /// Seed of the DLMM `ClaimFeeOperator` PDA, `[b"cf_operator", operator]`
+    pub const CLAIM_FEE_OPERATOR_SEED: &[u8] = b"cf_operator";
+
+    /// Marks a `#[repr(C)]` view as a DLMM zero-copy account. `Owner` names the
+    /// mainnet program for off-chain readers; on-chain, the owner is checked
+    /// against the DLMM program being invoked and the view read with `load_account`.
+    macro_rules! dlmm_account {
+        ($name:ident, $discriminator:expr) => {
+            impl Discriminator for $name {
+                const DISCRIMINATOR: &'static [u8] = &$discriminator;
+            }
+
+            impl Owner for $name {
+                fn owner() -> Pubkey {
+                    DLMM_PROGRAM_ID
+                }
+            }
+
+            impl ZeroCopy for $name {}
+        };
+    }
+
+    /// Decode a DLMM zero-copy account from raw account data (discriminator included)
+    pub fn load_from_bytes<T: ZeroCopy>(data: &[u8]) -> Result<&T> {
+        let disc = T::DISCRIMINATOR;
+        let end = disc.len() + std::mem::size_of::<T>();
+        require!(data.len() >= end, ErrorCode::AccountDidNotDeserialize);
+        require!(&data[..disc.len()] == disc, ErrorCode::AccountDiscriminatorMismatch);
+
+        bytemuck::try_from_bytes(&data[disc.len()..end])
+            .map_err(|_| error!(ErrorCode::AccountDidNotDeserialize))
+    }
+
+    /// Borrow a DLMM zero-copy account after checking its discriminator and
+    /// size. The caller checks the owner.
+    pub fn load_account<'a, T: ZeroCopy>(account: &'a AccountInfo) -> Result<std::cell::Ref<'a, T>> {
+        let data = account.try_borrow_data()?;
+        load_from_bytes::<T>(&data)?;
+
+        let start = T::DISCRIMINATOR.len();
+        Ok(std::cell::Ref::map(data, |data| bytemuck::from_bytes(&data[start..start + std::mem::size_of::<T>()])))
+    }
+
+    /// Little-endian `u128` with byte alignment.
+    ///
+    /// Account data is only guaranteed to be 8-byte aligned, so the views keep
+    /// 128-bit fields as raw bytes instead of relying on the target's `u128` alignment.
+    #[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Pod, Zeroable)]
+    #[repr(transparent)]
+    pub struct PodU128(pub [u8; 16]);
+
+    impl PodU128 {
+        pub fn get(&self) -> u128 {
+            u128::from_le_bytes(self.0)
+        }
+    }
+
+    impl From<u128> for PodU128 {
+        fn from(value: u128) -> Self {
+            Self(value.to_le_bytes())
+        }
+    }
+
+    #[derive(Copy, Clone, Debug, Pod, Zeroable)]
+    #[repr(C)]
     pub struct LbPair {
         pub parameters: PoolParameters,
         pub v_parameters: PoolVariableParameters,
@@ -18,70 +94,132 @@ pub mod meteora_dlmm_types {
         pub status: u8,
         pub require_base_factor_seed: u8,
         pub base_factor_seed: [u8; 2],
-        pub padding1: [u8; 2],
+        pub activation_type: u8,
+        pub creator_pool_on_off_control: u8,
         pub token_x_mint: Pubkey,
         pub token_y_mint: Pubkey,
         pub reserve_x: Pubkey,
         pub reserve_y: Pubkey,
         pub protocol_fee: ProtocolFee,
-        pub fee_owner: Pubkey,
-        pub reward_infos: [RewardInfo; 2],
+        /// The pool's fee owner in earlier DLMM versions, unused since protocol
+        /// fees are claimed by `ClaimFeeOperator` holders
+        pub padding1: [u8; 32],
+        pub reward_infos: [RewardInfo; NUM_REWARDS],
         pub oracle: Pubkey,
-        pub bin_array_bitmap: [u64; 512],
+        pub bin_array_bitmap: [u64; 16],
         pub last_updated_at: i64,
         pub whitelisted_wallet: Pubkey,
         pub pre_activation_swap_address: Pubkey,
         pub base_key: Pubkey,
-        pub activation_type: u8,
-        pub padding2: [u8; 1],
         pub activation_point: u64,
-        pub padding3: [u8; 24],
+        pub pre_activation_duration: u64,
+        pub padding3: [u8; 8],
+        pub padding4: u64,
+        pub creator: Pubkey,
+        pub token_mint_x_program_flag: u8,
+        pub token_mint_y_program_flag: u8,
+        pub reserved: [u8; 22],
     }
 
-    #[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+    dlmm_account!(LbPair, [33, 11, 49, 98, 181, 101, 177, 13]);
+
+    /// Grants `operator` the right to claim protocol fees, created by the DLMM admin
+    #[derive(Copy, Clone, Debug, Pod, Zeroable)]
+    #[repr(C)]
+    pub struct ClaimFeeOperator {
+        pub operator: Pubkey,
+        pub padding: [u8; 128],
+    }
+
+    dlmm_account!(ClaimFeeOperator, [166, 48, 134, 86, 34, 200, 188, 150]);
+
+    #[derive(Copy, Clone, Debug, Pod, Zeroable)]
+    #[repr(C)]
     pub struct PoolParameters {
         pub base_factor: u16,
         pub filter_period: u16,
         pub decay_period: u16,
         pub reduction_factor: u16,
         pub variable_fee_control: u32,
-        pub protocol_share: u16,
         pub max_volatility_accumulator: u32,
+        pub min_bin_id: i32,
+        pub max_bin_id: i32,
+        pub protocol_share: u16,
+        pub base_fee_power_factor: u8,
+        pub padding: [u8; 5],
     }
 
-    #[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+    #[derive(Copy, Clone, Debug, Pod, Zeroable)]
+    #[repr(C)]
     pub struct PoolVariableParameters {
         pub volatility_accumulator: u32,
         pub volatility_reference: u32,
         pub id_reference: i32,
+        pub padding: [u8; 4],
         pub time_of_last_update: i64,
+        pub padding1: [u8; 8],
     }
 
-    #[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+    #[derive(Copy, Clone, Debug, Pod, Zeroable)]
+    #[repr(C)]
     pub struct ProtocolFee {
         pub amount_x: u64,
         pub amount_y: u64,
     }
 
-    #[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+    /// Number of farming reward slots on every pool
+    pub const NUM_REWARDS: usize = 2;
+
+    #[derive(Copy, Clone, Debug, Pod, Zeroable)]
+    #[repr(C)]
     pub struct RewardInfo {
         pub mint: Pubkey,
         pub vault: Pubkey,
         pub funder: Pubkey,
         pub reward_duration: u64,
         pub reward_duration_end: u64,
-        pub reward_rate: u128,
+        pub reward_rate: PodU128,
         pub last_update_time: u64,
         pub cumulative_seconds_with_empty_liquidity_reward: u64,
     }
 
-    #[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+    impl RewardInfo {
+        /// Whether the pool has initialized this reward slot
+        pub fn initialized(&self) -> bool {
+            self.mint != Pubkey::default()
+        }
+    }
+
+    /// Number of bins stored inline in every position account
+    pub const DEFAULT_BIN_PER_POSITION: usize = 70;
+
+    #[derive(Copy, Clone, Debug, Pod, Zeroable)]
+    #[repr(C)]
     pub struct Position {
         pub lb_pair: Pubkey,
         pub owner: Pubkey,
-        pub liquidity_shares: [u64; 70],
-        pub reward_infos: [UserRewardInfo; 2],
-        pub fee_infos: [FeeInfo; 2],
+        pub liquidity_shares: [u64; DEFAULT_BIN_PER_POSITION],
+        pub reward_infos: [UserRewardInfo; DEFAULT_BIN_PER_POSITION],
+        pub fee_infos: [FeeInfo; DEFAULT_BIN_PER_POSITION],
+        pub lower_bin_id: i32,
+        pub upper_bin_id: i32,
+        pub last_updated_at: i64,
+        pub total_claimed_fee_x_amount: u64,
+        pub total_claimed_fee_y_amount: u64,
+        pub total_claimed_rewards: [u64; 2],
+        pub reserved: [u8; 160],
+    }
+
+    dlmm_account!(Position, [170, 188, 143, 228, 122, 64, 247, 208]);
+
+    #[derive(Copy, Clone, Debug, Pod, Zeroable)]
+    #[repr(C)]
+    pub struct PositionV2 {
+        pub lb_pair: Pubkey,
+        pub owner: Pubkey,
+        pub liquidity_shares: [PodU128; DEFAULT_BIN_PER_POSITION],
+        pub reward_infos: [UserRewardInfo; DEFAULT_BIN_PER_POSITION],
+        pub fee_infos: [FeeInfo; DEFAULT_BIN_PER_POSITION],
         pub lower_bin_id: i32,
         pub upper_bin_id: i32,
         pub last_updated_at: i64,
@@ -90,21 +228,134 @@ pub mod meteora_dlmm_types {
         pub total_claimed_rewards: [u64; 2],
         pub operator: Pubkey,
         pub lock_release_point: u64,
-        pub subjected_to_bootstrap_liquidity_locking: u8,
+        pub padding0: u8,
         pub fee_owner: Pubkey,
-        pub padding: [u8; 87],
+        pub reserved: [u8; 87],
     }
 
-    #[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+    dlmm_account!(PositionV2, [117, 176, 212, 199, 245, 180, 133, 182]);
+
+    /// State of one bin beyond the first `DEFAULT_BIN_PER_POSITION`. Extended
+    /// `PositionV2` accounts store these right after the fixed layout.
+    #[derive(Copy, Clone, Debug, Pod, Zeroable)]
+    #[repr(C)]
+    pub struct PositionBinData {
+        pub liquidity_share: PodU128,
+        pub reward_info: UserRewardInfo,
+        pub fee_info: FeeInfo,
+    }
+
+    /// A DLMM position of either layout, selected by its discriminator
+    #[derive(Copy, Clone, Debug)]
+    pub enum DlmmPosition<'a> {
+        V1(&'a Position),
+        V2 {
+            position: &'a PositionV2,
+            extension: &'a [PositionBinData],
+        },
+    }
+
+    impl<'a> DlmmPosition<'a> {
+        /// Decode raw position account data (discriminator included)
+        pub fn from_bytes(data: &'a [u8]) -> Result<Self> {
+            require!(data.len() >= 8, ErrorCode::AccountDiscriminatorNotFound);
+
+            if &data[..8] == Position::DISCRIMINATOR {
+                return Ok(Self::V1(load_from_bytes::<Position>(data)?));
+            }
+            require!(&data[..8] == PositionV2::DISCRIMINATOR, ErrorCode::AccountDiscriminatorMismatch);
+
+            let position = load_from_bytes::<PositionV2>(data)?;
+            let start = 8 + std::mem::size_of::<PositionV2>();
+            let width = position_width(position.lower_bin_id, position.upper_bin_id)?;
+            let end = start + width.saturating_sub(DEFAULT_BIN_PER_POSITION) * std::mem::size_of::<PositionBinData>();
+            require!(data.len() >= end, ErrorCode::AccountDidNotDeserialize);
+
+            let extension = bytemuck::try_cast_slice(&data[start..end])
+                .map_err(|_| error!(ErrorCode::AccountDidNotDeserialize))?;
+            Ok(Self::V2 { position, extension })
+        }
+
+        pub fn lb_pair(&self) -> Pubkey {
+            match self {
+                Self::V1(position) => position.lb_pair,
+                Self::V2 { position, .. } => position.lb_pair,
+            }
+        }
+
+        pub fn owner(&self) -> Pubkey {
+            match self {
+                Self::V1(position) => position.owner,
+                Self::V2 { position, .. } => position.owner,
+            }
+        }
+
+        pub fn lower_bin_id(&self) -> i32 {
+            match self {
+                Self::V1(position) => position.lower_bin_id,
+                Self::V2 { position, .. } => position.lower_bin_id,
+            }
+        }
+
+        pub fn upper_bin_id(&self) -> i32 {
+            match self {
+                Self::V1(position) => position.upper_bin_id,
+                Self::V2 { position, .. } => position.upper_bin_id,
+            }
+        }
+
+        /// Fee state for every bin in the position, inline bins first
+        pub fn fee_infos(&self) -> impl Iterator<Item = &'a FeeInfo> {
+            let (inline, extension): (&'a [FeeInfo], &'a [PositionBinData]) = match *self {
+                Self::V1(position) => (&position.fee_infos, &[]),
+                Self::V2 { position, extension } => (&position.fee_infos, extension),
+            };
+            inline.iter().chain(extension.iter().map(|bin| &bin.fee_info))
+        }
+
+        /// Indexes of every bin array the position's bin range touches
+        pub fn bin_array_indexes(&self) -> std::ops::RangeInclusive<i64> {
+            bin_array_index(self.lower_bin_id())..=bin_array_index(self.upper_bin_id())
+        }
+
+        /// Whether any bin in the position still holds liquidity shares
+        pub fn has_liquidity(&self) -> bool {
+            match *self {
+                Self::V1(position) => position.liquidity_shares.iter().any(|share| *share != 0),
+                Self::V2 { position, extension } => position.liquidity_shares
+                    .iter()
+                    .chain(extension.iter().map(|bin| &bin.liquidity_share))
+                    .any(|share| share.get() != 0),
+            }
+        }
+    }
+
+    /// Number of bins covered by a position
+    pub fn position_width(lower_bin_id: i32, upper_bin_id: i32) -> Result<usize> {
+        require!(upper_bin_id >= lower_bin_id, ErrorCode::AccountDidNotDeserialize);
+        Ok((upper_bin_id as i64 - lower_bin_id as i64) as usize + 1)
+    }
+
+    /// Number of bins stored in every bin array
+    pub const MAX_BIN_PER_ARRAY: i64 = 70;
+
+    /// Index of the bin array holding `bin_id`, with negative bins rounding down
+    pub fn bin_array_index(bin_id: i32) -> i64 {
+        (bin_id as i64).div_euclid(MAX_BIN_PER_ARRAY)
+    }
+
+    #[derive(Copy, Clone, Debug, Pod, Zeroable)]
+    #[repr(C)]
     pub struct UserRewardInfo {
-        pub reward_per_token_complete: u128,
-        pub reward_pending: u64,
+        pub reward_per_token_completes: [PodU128; 2],
+        pub reward_pendings: [u64; 2],
     }
 
-    #[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+    #[derive(Copy, Clone, Debug, Pod, Zeroable)]
+    #[repr(C)]
     pub struct FeeInfo {
-        pub fee_x_per_token_complete: u128,
-        pub fee_y_per_token_complete: u128,
+        pub fee_x_per_token_complete: PodU128,
+        pub fee_y_per_token_complete: PodU128,
         pub fee_x_pending: u64,
         pub fee_y_pending: u64,
     }
@@ -112,114 +363,343 @@ pub mod meteora_dlmm_types {
 
 pub mod cpi_instructions {
     use super::*;
-    use meteora_dlmm_types::*;
+    use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+    /// Purpose of a slice of remaining accounts passed to DLMM's `*2` instructions
+    #[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+    pub enum AccountsType {
+        TransferHookX,
+        TransferHookY,
+        TransferHookReward,
+    }
 
+    #[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+    pub struct RemainingAccountsSlice {
+        pub accounts_type: AccountsType,
+        pub length: u8,
+    }
+
+    /// Layout of the remaining accounts that precede the bin arrays
+    #[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default, PartialEq, Eq)]
+    pub struct RemainingAccountsInfo {
+        pub slices: Vec<RemainingAccountsSlice>,
+    }
+
+    /// Accounts for DLMM `claim_fee2`, which supports SPL Token and Token-2022
+    /// mints. Bin arrays covering the claimed range go in the CPI's remaining accounts.
     #[derive(Accounts)]
-    pub struct ClaimFee<'info> {
-        pub position: Account<'info, Position>,
+    pub struct ClaimFee2<'info> {
+        /// CHECK: DLMM pool, validated by the caller and by the DLMM program
         #[account(mut)]
-        pub lb_pair: Account<'info, LbPair>,
+        pub lb_pair: AccountInfo<'info>,
+        /// CHECK: DLMM `Position` or `PositionV2`, validated by the caller and by the DLMM program
         #[account(mut)]
-        pub user_token_x: Box<Account<'info, anchor_spl::token::TokenAccount>>,
+        pub position: AccountInfo<'info>,
+        /// CHECK: Position owner, signs through the CPI signer seeds
+        pub owner: AccountInfo<'info>,
         #[account(mut)]
-        pub user_token_y: Box<Account<'info, anchor_spl::token::TokenAccount>>,
+        pub reserve_x: Box<InterfaceAccount<'info, TokenAccount>>,
         #[account(mut)]
-        pub reserve_x: Box<Account<'info, anchor_spl::token::TokenAccount>>,
+        pub reserve_y: Box<InterfaceAccount<'info, TokenAccount>>,
         #[account(mut)]
-        pub reserve_y: Box<Account<'info, anchor_spl::token::TokenAccount>>,
-        pub token_x_mint: Box<Account<'info, anchor_spl::token::Mint>>,
-        pub token_y_mint: Box<Account<'info, anchor_spl::token::Mint>>,
-        pub token_program: Program<'info, anchor_spl::token::Token>,
-        pub owner: Signer<'info>,
+        pub user_token_x: Box<InterfaceAccount<'info, TokenAccount>>,
+        #[account(mut)]
+        pub user_token_y: Box<InterfaceAccount<'info, TokenAccount>>,
+        pub token_x_mint: Box<InterfaceAccount<'info, Mint>>,
+        pub token_y_mint: Box<InterfaceAccount<'info, Mint>>,
+        pub token_program_x: Interface<'info, TokenInterface>,
+        pub token_program_y: Interface<'info, TokenInterface>,
+        /// CHECK: SPL Memo program, required by DLMM for Token-2022 transfers
+        pub memo_program: AccountInfo<'info>,
+        /// CHECK: DLMM event authority PDA
+        pub event_authority: AccountInfo<'info>,
     }
 
-    pub fn claim_fee(ctx: CpiContext<ClaimFee>) -> Result<()> {
+    pub fn claim_fee2<'info>(
+        ctx: CpiContext<'_, '_, '_, 'info, ClaimFee2<'info>>,
+        min_bin_id: i32,
+        max_bin_id: i32,
+        remaining_accounts_info: RemainingAccountsInfo,
+    ) -> Result<()> {
+        let mut data = vec![112, 191, 101, 171, 28, 144, 127, 187]; // claim_fee2 discriminator
+        min_bin_id.serialize(&mut data)?;
+        max_bin_id.serialize(&mut data)?;
+        remaining_accounts_info.serialize(&mut data)?;
+
+        let mut accounts = vec![
+            AccountMeta::new(ctx.accounts.lb_pair.key(), false),
+            AccountMeta::new(ctx.accounts.position.key(), false),
+            AccountMeta::new_readonly(ctx.accounts.owner.key(), true),
+            AccountMeta::new(ctx.accounts.reserve_x.key(), false),
+            AccountMeta::new(ctx.accounts.reserve_y.key(), false),
+            AccountMeta::new(ctx.accounts.user_token_x.key(), false),
+            AccountMeta::new(ctx.accounts.user_token_y.key(), false),
+            AccountMeta::new_readonly(ctx.accounts.token_x_mint.key(), false),
+            AccountMeta::new_readonly(ctx.accounts.token_y_mint.key(), false),
+            AccountMeta::new_readonly(ctx.accounts.token_program_x.key(), false),
+            AccountMeta::new_readonly(ctx.accounts.token_program_y.key(), false),
+            AccountMeta::new_readonly(ctx.accounts.memo_program.key(), false),
+            AccountMeta::new_readonly(ctx.accounts.event_authority.key(), false),
+            AccountMeta::new_readonly(ctx.program.key(), false),
+        ];
+        let mut account_infos = vec![
+            ctx.accounts.lb_pair.to_account_info(),
+            ctx.accounts.position.to_account_info(),
+            ctx.accounts.owner.to_account_info(),
+            ctx.accounts.reserve_x.to_account_info(),
+            ctx.accounts.reserve_y.to_account_info(),
+            ctx.accounts.user_token_x.to_account_info(),
+            ctx.accounts.user_token_y.to_account_info(),
+            ctx.accounts.token_x_mint.to_account_info(),
+            ctx.accounts.token_y_mint.to_account_info(),
+            ctx.accounts.token_program_x.to_account_info(),
+            ctx.accounts.token_program_y.to_account_info(),
+            ctx.accounts.memo_program.to_account_info(),
+            ctx.accounts.event_authority.to_account_info(),
+            ctx.program.to_account_info(),
+        ];
+        append_remaining_accounts(&mut accounts, &mut account_infos, ctx.remaining_accounts);
+
         let ix = anchor_lang::solana_program::instruction::Instruction {
-            program_id: DLMM_PROGRAM_ID,
-            accounts: vec![
-                AccountMeta::new_readonly(ctx.accounts.position.key(), false),
-                AccountMeta::new(ctx.accounts.lb_pair.key(), false),
-                AccountMeta::new(ctx.accounts.user_token_x.key(), false),
-                AccountMeta::new(ctx.accounts.user_token_y.key(), false),
-                AccountMeta::new(ctx.accounts.reserve_x.key(), false),
-                AccountMeta::new(ctx.accounts.reserve_y.key(), false),
-                AccountMeta::new_readonly(ctx.accounts.token_x_mint.key(), false),
-                AccountMeta::new_readonly(ctx.accounts.token_y_mint.key(), false),
-                AccountMeta::new_readonly(ctx.accounts.token_program.key(), false),
-                AccountMeta::new_readonly(ctx.accounts.owner.key(), true),
-            ],
-            data: vec![0x86, 0x03, 0x22, 0x39, 0xac, 0x49, 0x61, 0x95], // claim_fee discriminator
+            program_id: ctx.program.key(),
+            accounts,
+            data,
         };
-
-        anchor_lang::solana_program::program::invoke(
-            &ix,
-            &[
-                ctx.accounts.position.to_account_info(),
-                ctx.accounts.lb_pair.to_account_info(),
-                ctx.accounts.user_token_x.to_account_info(),
-                ctx.accounts.user_token_y.to_account_info(),
-                ctx.accounts.reserve_x.to_account_info(),
-                ctx.accounts.reserve_y.to_account_info(),
-                ctx.accounts.token_x_mint.to_account_info(),
-                ctx.accounts.token_y_mint.to_account_info(),
-                ctx.accounts.token_program.to_account_info(),
-                ctx.accounts.owner.to_account_info(),
-            ],
-        )?;
+        anchor_lang::solana_program::program::invoke_signed(&ix, &account_infos, ctx.signer_seeds)?;
 
         Ok(())
     }
 
+    /// Accounts for DLMM `claim_protocol_fee`. The pool's protocol fees go to
+    /// `operator`'s receiver accounts, where `operator` holds a
+    /// `ClaimFeeOperator` account the DLMM admin created for it. Transfer hook
+    /// accounts go in the CPI's remaining accounts.
     #[derive(Accounts)]
     pub struct ClaimProtocolFee<'info> {
+        /// CHECK: DLMM pool, validated by the caller and by the DLMM program
+        #[account(mut)]
+        pub lb_pair: AccountInfo<'info>,
+        #[account(mut)]
+        pub reserve_x: Box<InterfaceAccount<'info, TokenAccount>>,
         #[account(mut)]
-        pub lb_pair: Account<'info, LbPair>,
+        pub reserve_y: Box<InterfaceAccount<'info, TokenAccount>>,
+        pub token_x_mint: Box<InterfaceAccount<'info, Mint>>,
+        pub token_y_mint: Box<InterfaceAccount<'info, Mint>>,
+        #[account(mut)]
+        pub receiver_token_x: Box<InterfaceAccount<'info, TokenAccount>>,
+        #[account(mut)]
+        pub receiver_token_y: Box<InterfaceAccount<'info, TokenAccount>>,
+        /// CHECK: `ClaimFeeOperator` of `operator`, validated by the caller and by the DLMM program
+        pub claim_fee_operator: AccountInfo<'info>,
+        /// CHECK: Claim fee operator, signs through the CPI signer seeds
+        pub operator: AccountInfo<'info>,
+        pub token_x_program: Interface<'info, TokenInterface>,
+        pub token_y_program: Interface<'info, TokenInterface>,
+        /// CHECK: SPL Memo program, required by DLMM for Token-2022 transfers
+        pub memo_program: AccountInfo<'info>,
+    }
+
+    pub fn claim_protocol_fee<'info>(
+        ctx: CpiContext<'_, '_, '_, 'info, ClaimProtocolFee<'info>>,
+        max_amount_x: u64,
+        max_amount_y: u64,
+        remaining_accounts_info: RemainingAccountsInfo,
+    ) -> Result<()> {
+        let mut data = vec![165, 228, 133, 48, 99, 249, 255, 33]; // claim_protocol_fee discriminator
+        max_amount_x.serialize(&mut data)?;
+        max_amount_y.serialize(&mut data)?;
+        remaining_accounts_info.serialize(&mut data)?;
+
+        let mut accounts = vec![
+            AccountMeta::new(ctx.accounts.lb_pair.key(), false),
+            AccountMeta::new(ctx.accounts.reserve_x.key(), false),
+            AccountMeta::new(ctx.accounts.reserve_y.key(), false),
+            AccountMeta::new_readonly(ctx.accounts.token_x_mint.key(), false),
+            AccountMeta::new_readonly(ctx.accounts.token_y_mint.key(), false),
+            AccountMeta::new(ctx.accounts.receiver_token_x.key(), false),
+            AccountMeta::new(ctx.accounts.receiver_token_y.key(), false),
+            AccountMeta::new_readonly(ctx.accounts.claim_fee_operator.key(), false),
+            AccountMeta::new_readonly(ctx.accounts.operator.key(), true),
+            AccountMeta::new_readonly(ctx.accounts.token_x_program.key(), false),
+            AccountMeta::new_readonly(ctx.accounts.token_y_program.key(), false),
+            AccountMeta::new_readonly(ctx.accounts.memo_program.key(), false),
+        ];
+        let mut account_infos = vec![
+            ctx.accounts.lb_pair.to_account_info(),
+            ctx.accounts.reserve_x.to_account_info(),
+            ctx.accounts.reserve_y.to_account_info(),
+            ctx.accounts.token_x_mint.to_account_info(),
+            ctx.accounts.token_y_mint.to_account_info(),
+            ctx.accounts.receiver_token_x.to_account_info(),
+            ctx.accounts.receiver_token_y.to_account_info(),
+            ctx.accounts.claim_fee_operator.to_account_info(),
+            ctx.accounts.operator.to_account_info(),
+            ctx.accounts.token_x_program.to_account_info(),
+            ctx.accounts.token_y_program.to_account_info(),
+            ctx.accounts.memo_program.to_account_info(),
+        ];
+        append_remaining_accounts(&mut accounts, &mut account_infos, ctx.remaining_accounts);
+
+        let ix = anchor_lang::solana_program::instruction::Instruction {
+            program_id: ctx.program.key(),
+            accounts,
+            data,
+        };
+        anchor_lang::solana_program::program::invoke_signed(&ix, &account_infos, ctx.signer_seeds)?;
+
+        Ok(())
+    }
+
+    /// Accounts for DLMM `claim_reward2`. Bin arrays covering the claimed range
+    /// go in the CPI's remaining accounts.
+    #[derive(Accounts)]
+    pub struct ClaimReward2<'info> {
+        /// CHECK: DLMM pool, validated by the caller and by the DLMM program
         #[account(mut)]
-        pub reserve_x: Box<Account<'info, anchor_spl::token::TokenAccount>>,
+        pub lb_pair: AccountInfo<'info>,
+        /// CHECK: DLMM `Position` or `PositionV2`, validated by the caller and by the DLMM program
         #[account(mut)]
-        pub reserve_y: Box<Account<'info, anchor_spl::token::TokenAccount>>,
+        pub position: AccountInfo<'info>,
+        /// CHECK: Position owner, signs through the CPI signer seeds
+        pub owner: AccountInfo<'info>,
+        /// CHECK: Pool reward vault, checked against `LbPair.reward_infos` by the caller
         #[account(mut)]
-        pub fee_recipient_token_x: Box<Account<'info, anchor_spl::token::TokenAccount>>,
+        pub reward_vault: AccountInfo<'info>,
+        /// CHECK: Reward mint, checked against `LbPair.reward_infos` by the caller
+        pub reward_mint: AccountInfo<'info>,
+        /// CHECK: Vault-owned reward token account, checked by the caller
         #[account(mut)]
-        pub fee_recipient_token_y: Box<Account<'info, anchor_spl::token::TokenAccount>>,
-        pub token_x_mint: Box<Account<'info, anchor_spl::token::Mint>>,
-        pub token_y_mint: Box<Account<'info, anchor_spl::token::Mint>>,
-        pub token_program: Program<'info, anchor_spl::token::Token>,
-        pub fee_owner: Signer<'info>,
+        pub user_token_account: AccountInfo<'info>,
+        /// CHECK: Token program owning the reward mint, checked by the caller
+        pub token_program: AccountInfo<'info>,
+        /// CHECK: SPL Memo program, required by DLMM for Token-2022 transfers
+        pub memo_program: AccountInfo<'info>,
+        /// CHECK: DLMM event authority PDA
+        pub event_authority: AccountInfo<'info>,
     }
 
-    pub fn claim_protocol_fee(ctx: CpiContext<ClaimProtocolFee>) -> Result<()> {
+    pub fn claim_reward2<'info>(
+        ctx: CpiContext<'_, '_, '_, 'info, ClaimReward2<'info>>,
+        reward_index: u64,
+        min_bin_id: i32,
+        max_bin_id: i32,
+        remaining_accounts_info: RemainingAccountsInfo,
+    ) -> Result<()> {
+        let mut data = vec![190, 3, 127, 119, 178, 87, 157, 183]; // claim_reward2 discriminator
+        reward_index.serialize(&mut data)?;
+        min_bin_id.serialize(&mut data)?;
+        max_bin_id.serialize(&mut data)?;
+        remaining_accounts_info.serialize(&mut data)?;
+
+        let mut accounts = vec![
+            AccountMeta::new(ctx.accounts.lb_pair.key(), false),
+            AccountMeta::new(ctx.accounts.position.key(), false),
+            AccountMeta::new_readonly(ctx.accounts.owner.key(), true),
+            AccountMeta::new(ctx.accounts.reward_vault.key(), false),
+            AccountMeta::new_readonly(ctx.accounts.reward_mint.key(), false),
+            AccountMeta::new(ctx.accounts.user_token_account.key(), false),
+            AccountMeta::new_readonly(ctx.accounts.token_program.key(), false),
+            AccountMeta::new_readonly(ctx.accounts.memo_program.key(), false),
+            AccountMeta::new_readonly(ctx.accounts.event_authority.key(), false),
+            AccountMeta::new_readonly(ctx.program.key(), false),
+        ];
+        let mut account_infos = vec![
+            ctx.accounts.lb_pair.to_account_info(),
+            ctx.accounts.position.to_account_info(),
+            ctx.accounts.owner.to_account_info(),
+            ctx.accounts.reward_vault.to_account_info(),
+            ctx.accounts.reward_mint.to_account_info(),
+            ctx.accounts.user_token_account.to_account_info(),
+            ctx.accounts.token_program.to_account_info(),
+            ctx.accounts.memo_program.to_account_info(),
+            ctx.accounts.event_authority.to_account_info(),
+            ctx.program.to_account_info(),
+        ];
+        append_remaining_accounts(&mut accounts, &mut account_infos, ctx.remaining_accounts);
+
         let ix = anchor_lang::solana_program::instruction::Instruction {
-            program_id: DLMM_PROGRAM_ID,
-            accounts: vec![
-                AccountMeta::new(ctx.accounts.lb_pair.key(), false),
-                AccountMeta::new(ctx.accounts.reserve_x.key(), false),
-                AccountMeta::new(ctx.accounts.reserve_y.key(), false),
-                AccountMeta::new(ctx.accounts.fee_recipient_token_x.key(), false),
-                AccountMeta::new(ctx.accounts.fee_recipient_token_y.key(), false),
-                AccountMeta::new_readonly(ctx.accounts.token_x_mint.key(), false),
-                AccountMeta::new_readonly(ctx.accounts.token_y_mint.key(), false),
-                AccountMeta::new_readonly(ctx.accounts.token_program.key(), false),
-                AccountMeta::new_readonly(ctx.accounts.fee_owner.key(), true),
-            ],
-            data: vec![0xee, 0x58, 0x3b, 0x31, 0xa4, 0x2e, 0x8c, 0x88], // claim_protocol_fee discriminator
+            program_id: ctx.program.key(),
+            accounts,
+            data,
         };
+        anchor_lang::solana_program::program::invoke_signed(&ix, &account_infos, ctx.signer_seeds)?;
 
-        anchor_lang::solana_program::program::invoke(
-            &ix,
-            &[
-                ctx.accounts.lb_pair.to_account_info(),
-                ctx.accounts.reserve_x.to_account_info(),
-                ctx.accounts.reserve_y.to_account_info(),
-                ctx.accounts.fee_recipient_token_x.to_account_info(),
-                ctx.accounts.fee_recipient_token_y.to_account_info(),
-                ctx.accounts.token_x_mint.to_account_info(),
-                ctx.accounts.token_y_mint.to_account_info(),
-                ctx.accounts.token_program.to_account_info(),
-                ctx.accounts.fee_owner.to_account_info(),
-            ],
-        )?;
+        Ok(())
+    }
+
+    /// Accounts for DLMM `initialize_position`, which creates a position at a
+    /// fresh keypair for `owner`
+    #[derive(Accounts)]
+    pub struct InitializePosition<'info> {
+        /// CHECK: Pays the position's rent, signs the outer transaction
+        #[account(mut)]
+        pub payer: AccountInfo<'info>,
+        /// CHECK: New position keypair, signs the outer transaction
+        #[account(mut)]
+        pub position: AccountInfo<'info>,
+        /// CHECK: DLMM pool, validated by the caller and by the DLMM program
+        pub lb_pair: AccountInfo<'info>,
+        /// CHECK: Position owner, signs through the CPI signer seeds
+        pub owner: AccountInfo<'info>,
+        pub system_program: Program<'info, System>,
+        pub rent: Sysvar<'info, Rent>,
+        /// CHECK: DLMM event authority PDA
+        pub event_authority: AccountInfo<'info>,
+    }
+
+    pub fn initialize_position<'info>(
+        ctx: CpiContext<'_, '_, '_, 'info, InitializePosition<'info>>,
+        lower_bin_id: i32,
+        width: i32,
+    ) -> Result<()> {
+        let mut data = vec![219, 192, 234, 71, 190, 191, 102, 80]; // initialize_position discriminator
+        lower_bin_id.serialize(&mut data)?;
+        width.serialize(&mut data)?;
+
+        let accounts = vec![
+            AccountMeta::new(ctx.accounts.payer.key(), true),
+            AccountMeta::new(ctx.accounts.position.key(), true),
+            AccountMeta::new_readonly(ctx.accounts.lb_pair.key(), false),
+            AccountMeta::new_readonly(ctx.accounts.owner.key(), true),
+            AccountMeta::new_readonly(ctx.accounts.system_program.key(), false),
+            AccountMeta::new_readonly(ctx.accounts.rent.key(), false),
+            AccountMeta::new_readonly(ctx.accounts.event_authority.key(), false),
+            AccountMeta::new_readonly(ctx.program.key(), false),
+        ];
+        let account_infos = vec![
+            ctx.accounts.payer.to_account_info(),
+            ctx.accounts.position.to_account_info(),
+            ctx.accounts.lb_pair.to_account_info(),
+            ctx.accounts.owner.to_account_info(),
+            ctx.accounts.system_program.to_account_info(),
+            ctx.accounts.rent.to_account_info(),
+            ctx.accounts.event_authority.to_account_info(),
+            ctx.program.to_account_info(),
+        ];
+
+        let ix = anchor_lang::solana_program::instruction::Instruction {
+            program_id: ctx.program.key(),
+            accounts,
+            data,
+        };
+        anchor_lang::solana_program::program::invoke_signed(&ix, &account_infos, ctx.signer_seeds)?;
 
         Ok(())
     }
-}
\ No newline at end of file
+
+    /// Forward CPI remaining accounts (transfer hook accounts, then bin arrays) to DLMM
+    fn append_remaining_accounts<'info>(
+        accounts: &mut Vec<AccountMeta>,
+        account_infos: &mut Vec<AccountInfo<'info>>,
+        remaining_accounts: Vec<AccountInfo<'info>>,
+    ) {
+        for account in remaining_accounts {
+            accounts.push(if account.is_writable {
+                AccountMeta::new(account.key(), false)
+            } else {
+                AccountMeta::new_readonly(account.key(), false)
+            });
+            account_infos.push(account);
+        }
+    }
+}
@@ -0,0 +1,306 @@
+use anchor_lang::prelude::*;
+
+// Every event carries the vault, its DLMM pool and the slot it was emitted in so
+// indexers can order and attribute them without re-reading account state.
+// Amounts are what actually moved; `*_transfer_fee` is what Token-2022 withheld
+// on top of them, and is zero for mints without the TransferFee extension.
+
+/// The program config was created or changed
+#[event]
+pub struct ProgramConfigUpdated {
+    pub admin: Pubkey,
+    pub protocol_fee_bps: u16,
+    pub allowed_dlmm_programs: Vec<Pubkey>,
+    pub default_config_delay: i64,
+    pub treasury: Pubkey,
+    pub slot: u64,
+}
+
+#[event]
+pub struct VaultInitialized {
+    pub fee_vault: Pubkey,
+    pub dlmm_pool: Pubkey,
+    pub authority: Pubkey,
+    pub base_mint: Pubkey,
+    pub quote_mint: Pubkey,
+    pub slot: u64,
+}
+
+/// LP position fees claimed into the vault, by one or a batch of positions
+#[event]
+pub struct FeesCollected {
+    pub fee_vault: Pubkey,
+    pub dlmm_pool: Pubkey,
+    pub positions: u8,
+    pub base_amount: u64,
+    pub quote_amount: u64,
+    pub base_transfer_fee: u64,
+    pub quote_transfer_fee: u64,
+    pub total_collected_base: u64,
+    pub total_collected_quote: u64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct ProtocolFeesCollected {
+    pub fee_vault: Pubkey,
+    pub dlmm_pool: Pubkey,
+    pub base_amount: u64,
+    pub quote_amount: u64,
+    pub base_transfer_fee: u64,
+    pub quote_transfer_fee: u64,
+    pub total_protocol_fees_base: u64,
+    pub total_protocol_fees_quote: u64,
+    pub slot: u64,
+}
+
+/// Protocol fee sent to the treasury out of a collection, before the rest was made routable
+#[event]
+pub struct ProtocolFeeSkimmed {
+    pub fee_vault: Pubkey,
+    pub dlmm_pool: Pubkey,
+    pub treasury: Pubkey,
+    pub protocol_fee_bps: u16,
+    pub base_amount: u64,
+    pub quote_amount: u64,
+    pub base_transfer_fee: u64,
+    pub quote_transfer_fee: u64,
+    pub total_skimmed_base: u64,
+    pub total_skimmed_quote: u64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct RewardCollected {
+    pub fee_vault: Pubkey,
+    pub dlmm_pool: Pubkey,
+    pub reward_index: u8,
+    pub reward_mint: Pubkey,
+    pub amount: u64,
+    pub transfer_fee: u64,
+    pub total_collected: u64,
+    pub slot: u64,
+}
+
+/// Fees pushed to a single recipient by `distribute_fees`
+#[event]
+pub struct FeesDistributed {
+    pub fee_vault: Pubkey,
+    pub dlmm_pool: Pubkey,
+    pub recipient: Pubkey,
+    pub base_amount: u64,
+    pub quote_amount: u64,
+    pub base_transfer_fee: u64,
+    pub quote_transfer_fee: u64,
+    pub slot: u64,
+}
+
+/// Fees split across the distribution config by `distribute_to_recipients`
+#[event]
+pub struct RecipientsDistributed {
+    pub fee_vault: Pubkey,
+    pub dlmm_pool: Pubkey,
+    pub recipients: u8,
+    pub base_amount: u64,
+    pub quote_amount: u64,
+    pub base_transfer_fee: u64,
+    pub quote_transfer_fee: u64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct RewardDistributed {
+    pub fee_vault: Pubkey,
+    pub dlmm_pool: Pubkey,
+    pub reward_index: u8,
+    pub reward_mint: Pubkey,
+    pub recipients: u8,
+    pub amount: u64,
+    pub transfer_fee: u64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct DistributionConfigUpdated {
+    pub fee_vault: Pubkey,
+    pub dlmm_pool: Pubkey,
+    pub recipients: Vec<RecipientWeight>,
+    pub slot: u64,
+}
+
+/// A recipient position was opened or had its shares changed
+#[event]
+pub struct RecipientSharesUpdated {
+    pub fee_vault: Pubkey,
+    pub dlmm_pool: Pubkey,
+    pub recipient: Pubkey,
+    pub shares: u64,
+    pub total_shares: u64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct FeesClaimed {
+    pub fee_vault: Pubkey,
+    pub dlmm_pool: Pubkey,
+    pub recipient: Pubkey,
+    pub base_amount: u64,
+    pub quote_amount: u64,
+    pub base_transfer_fee: u64,
+    pub quote_transfer_fee: u64,
+    pub total_claimed_base: u64,
+    pub total_claimed_quote: u64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct AuthorityProposed {
+    pub fee_vault: Pubkey,
+    pub dlmm_pool: Pubkey,
+    pub authority: Pubkey,
+    pub pending_authority: Pubkey,
+    pub slot: u64,
+}
+
+#[event]
+pub struct AuthorityTransferCancelled {
+    pub fee_vault: Pubkey,
+    pub dlmm_pool: Pubkey,
+    pub authority: Pubkey,
+    pub pending_authority: Pubkey,
+    pub slot: u64,
+}
+
+/// A v0 fee vault was reallocated to the current layout at its own address
+#[event]
+pub struct FeeVaultMigrated {
+    pub fee_vault: Pubkey,
+    pub dlmm_pool: Pubkey,
+    pub old_version: u8,
+    pub new_version: u8,
+    pub payer: Pubkey,
+    pub slot: u64,
+}
+
+/// The vault and its token accounts were closed and their rent returned
+#[event]
+pub struct VaultClosed {
+    pub fee_vault: Pubkey,
+    pub dlmm_pool: Pubkey,
+    pub authority: Pubkey,
+    pub rent_receiver: Pubkey,
+    pub slot: u64,
+}
+
+/// A DLMM position owned by the vault was registered for collection
+#[event]
+pub struct VaultPositionRegistered {
+    pub fee_vault: Pubkey,
+    pub dlmm_pool: Pubkey,
+    pub position: Pubkey,
+    pub position_count: u32,
+    pub slot: u64,
+}
+
+/// An emptied or closed DLMM position was taken off the vault
+#[event]
+pub struct VaultPositionDeregistered {
+    pub fee_vault: Pubkey,
+    pub dlmm_pool: Pubkey,
+    pub position: Pubkey,
+    pub position_count: u32,
+    pub slot: u64,
+}
+
+/// A fully claimed recipient position was closed and its shares removed
+#[event]
+pub struct RecipientPositionClosed {
+    pub fee_vault: Pubkey,
+    pub dlmm_pool: Pubkey,
+    pub recipient: Pubkey,
+    pub total_shares: u64,
+    pub rent_receiver: Pubkey,
+    pub slot: u64,
+}
+
+/// The pending authority accepted and took over the vault
+#[event]
+pub struct AuthorityChanged {
+    pub fee_vault: Pubkey,
+    pub dlmm_pool: Pubkey,
+    pub old_authority: Pubkey,
+    pub new_authority: Pubkey,
+    pub slot: u64,
+}
+
+/// A delegated role was granted or revoked
+#[event]
+pub struct RoleChanged {
+    pub fee_vault: Pubkey,
+    pub dlmm_pool: Pubkey,
+    pub role: Role,
+    pub old_holder: Option<Pubkey>,
+    pub new_holder: Option<Pubkey>,
+    pub slot: u64,
+}
+
+/// The authority or guardian replaced the vault's `PAUSE_*` flags
+#[event]
+pub struct PauseFlagsChanged {
+    pub fee_vault: Pubkey,
+    pub dlmm_pool: Pubkey,
+    pub old_flags: u8,
+    pub new_flags: u8,
+    pub authority: Pubkey,
+    pub slot: u64,
+}
+
+#[event]
+pub struct ConfigDelayChanged {
+    pub fee_vault: Pubkey,
+    pub dlmm_pool: Pubkey,
+    pub old_delay: i64,
+    pub new_delay: i64,
+    pub slot: u64,
+}
+
+/// A vault multisig was created or had its members changed
+#[event]
+pub struct MultisigUpdated {
+    pub fee_vault: Pubkey,
+    pub dlmm_pool: Pubkey,
+    pub multisig: Pubkey,
+    pub members: Vec<Pubkey>,
+    pub threshold: u8,
+    pub slot: u64,
+}
+
+/// A configuration change was scheduled; it can be executed from `eta` on
+#[event]
+pub struct ConfigChangeQueued {
+    pub fee_vault: Pubkey,
+    pub dlmm_pool: Pubkey,
+    pub id: u64,
+    pub change: ConfigChange,
+    pub eta: i64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct ConfigChangeExecuted {
+    pub fee_vault: Pubkey,
+    pub dlmm_pool: Pubkey,
+    pub id: u64,
+    pub executor: Pubkey,
+    pub slot: u64,
+}
+
+#[event]
+pub struct ConfigChangeCancelled {
+    pub fee_vault: Pubkey,
+    pub dlmm_pool: Pubkey,
+    pub id: u64,
+    pub slot: u64,
+}
+
+use crate::{ConfigChange, RecipientWeight, Role};
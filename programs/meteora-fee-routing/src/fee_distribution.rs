@@ -0,0 +1,355 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_2022::spl_token_2022::onchain::invoke_transfer_checked;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+/// Weights are expressed in basis points and must sum to this value
+pub const BPS_DENOMINATOR: u16 = 10_000;
+
+/// Maximum number of recipients a distribution config can hold
+pub const MAX_RECIPIENTS: usize = 10;
+
+/// A single entry in the split table
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq)]
+pub struct RecipientWeight {
+    pub recipient: Pubkey,
+    pub weight_bps: u16,
+}
+
+impl RecipientWeight {
+    pub const LEN: usize = 32 + // recipient
+        2;  // weight_bps
+}
+
+/// Weighted distribution of the fee vault balances to the configured recipients
+pub struct WeightedDistribution;
+
+impl WeightedDistribution {
+    /// Validate a split table before it is stored on-chain
+    pub fn validate_recipients(recipients: &[RecipientWeight]) -> Result<()> {
+        require!(
+            !recipients.is_empty() && recipients.len() <= MAX_RECIPIENTS,
+            FeeRoutingError::InvalidRecipientCount
+        );
+
+        let mut total_bps: u16 = 0;
+        for (i, entry) in recipients.iter().enumerate() {
+            require!(entry.weight_bps > 0, FeeRoutingError::InvalidRecipientWeights);
+            require!(
+                !recipients[..i].iter().any(|other| other.recipient == entry.recipient),
+                FeeRoutingError::DuplicateRecipient
+            );
+            total_bps = total_bps
+                .checked_add(entry.weight_bps)
+                .ok_or(FeeRoutingError::InvalidRecipientWeights)?;
+        }
+
+        require!(
+            total_bps == BPS_DENOMINATOR,
+            FeeRoutingError::InvalidRecipientWeights
+        );
+        Ok(())
+    }
+
+    /// Share of `amount` owed for a weight in basis points, rounded down
+    pub fn share_of(amount: u64, weight_bps: u16) -> Result<u64> {
+        let share = (amount as u128)
+            .checked_mul(weight_bps as u128)
+            .ok_or(FeeRoutingError::MathOverflow)?
+            / BPS_DENOMINATOR as u128;
+        u64::try_from(share).map_err(|_| error!(FeeRoutingError::MathOverflow))
+    }
+
+    /// Split the routable vault balances across every configured recipient.
+    ///
+    /// `remaining_accounts` must hold one `[base, quote]` token account pair per
+    /// recipient, in config order, followed by any transfer hook accounts.
+    /// Rounding dust stays in the vault and is picked up by the next distribution.
+    ///
+    /// Shares are split from the vault balance, so Token-2022 transfer fees come
+    /// out of each recipient's share. Returns what recipients received and the
+    /// fees withheld on the way.
+    pub fn distribute<'info>(
+        ctx: &Context<'_, '_, 'info, 'info, DistributeToRecipients<'info>>,
+    ) -> Result<FeeAmounts> {
+        let fee_vault = &ctx.accounts.fee_vault;
+        let recipients = &ctx.accounts.distribution_config.recipients;
+
+        require!(
+            ctx.remaining_accounts.len() >= recipients.len() * 2,
+            FeeRoutingError::InvalidRecipientAccount
+        );
+        let (recipient_accounts, hook_accounts) = ctx.remaining_accounts.split_at(recipients.len() * 2);
+
+        // Fees already owed to pull-based recipient positions are not routable here
+        let base_total = ctx.accounts.base_token_account.amount
+            .saturating_sub(fee_vault.reserved_base);
+        let quote_total = ctx.accounts.quote_token_account.amount
+            .saturating_sub(fee_vault.reserved_quote);
+
+        let mut distributed = FeeAmounts::default();
+
+        for (entry, accounts) in recipients.iter().zip(recipient_accounts.chunks(2)) {
+            let recipient_base = InterfaceAccount::<TokenAccount>::try_from(&accounts[0])?;
+            let recipient_quote = InterfaceAccount::<TokenAccount>::try_from(&accounts[1])?;
+
+            require_keys_eq!(
+                recipient_base.owner,
+                entry.recipient,
+                FeeRoutingError::InvalidRecipientAccount
+            );
+            require_keys_eq!(
+                recipient_base.mint,
+                fee_vault.base_mint,
+                FeeRoutingError::InvalidRecipientAccount
+            );
+            require_keys_eq!(
+                recipient_quote.owner,
+                entry.recipient,
+                FeeRoutingError::InvalidRecipientAccount
+            );
+            require_keys_eq!(
+                recipient_quote.mint,
+                fee_vault.quote_mint,
+                FeeRoutingError::InvalidRecipientAccount
+            );
+
+            let base_share = Self::share_of(base_total, entry.weight_bps)?;
+            let quote_share = Self::share_of(quote_total, entry.weight_bps)?;
+
+            let base_transfer_fee = transfer_from_vault(
+                &ctx.accounts.base_token_program,
+                fee_vault,
+                &ctx.accounts.base_mint,
+                ctx.accounts.base_token_account.to_account_info(),
+                accounts[0].clone(),
+                base_share,
+                hook_accounts,
+            )?;
+            let quote_transfer_fee = transfer_from_vault(
+                &ctx.accounts.quote_token_program,
+                fee_vault,
+                &ctx.accounts.quote_mint,
+                ctx.accounts.quote_token_account.to_account_info(),
+                accounts[1].clone(),
+                quote_share,
+                hook_accounts,
+            )?;
+
+            let received = FeeAmounts {
+                base: base_share.saturating_sub(base_transfer_fee),
+                quote: quote_share.saturating_sub(quote_transfer_fee),
+                base_transfer_fee,
+                quote_transfer_fee,
+            };
+            distributed = distributed.checked_add(received)?;
+
+            msg!(
+                "Recipient {} ({} bps): Base {} (transfer fee {}), Quote {} (transfer fee {})",
+                entry.recipient,
+                entry.weight_bps,
+                received.base,
+                base_transfer_fee,
+                received.quote,
+                quote_transfer_fee
+            );
+        }
+
+        Ok(distributed)
+    }
+
+    /// Split the vault's balance of a collected farming reward across every
+    /// configured recipient, with the same weights as the fee split.
+    ///
+    /// `remaining_accounts` must hold one reward token account per recipient,
+    /// in config order, followed by any transfer hook accounts. Returns what recipients received and the Token-2022
+    /// transfer fees withheld from it.
+    pub fn distribute_reward<'info>(
+        ctx: &Context<'_, '_, 'info, 'info, DistributeRewardsToRecipients<'info>>,
+    ) -> Result<(u64, u64)> {
+        let fee_vault = &ctx.accounts.fee_vault;
+        let recipients = &ctx.accounts.distribution_config.recipients;
+        let reward_mint = ctx.accounts.reward_mint.key();
+
+        require!(
+            ctx.remaining_accounts.len() >= recipients.len(),
+            FeeRoutingError::InvalidRecipientAccount
+        );
+        let (recipient_accounts, hook_accounts) = ctx.remaining_accounts.split_at(recipients.len());
+
+        let reward_total = ctx.accounts.reward_token_account.amount;
+        let mut reward_distributed: u64 = 0;
+        let mut transfer_fees: u64 = 0;
+
+        for (entry, account) in recipients.iter().zip(recipient_accounts.iter()) {
+            let recipient_reward = InterfaceAccount::<TokenAccount>::try_from(account)?;
+
+            require_keys_eq!(
+                recipient_reward.owner,
+                entry.recipient,
+                FeeRoutingError::InvalidRecipientAccount
+            );
+            require_keys_eq!(
+                recipient_reward.mint,
+                reward_mint,
+                FeeRoutingError::InvalidRecipientAccount
+            );
+
+            let reward_share = Self::share_of(reward_total, entry.weight_bps)?;
+
+            let transfer_fee = transfer_from_vault(
+                &ctx.accounts.token_program,
+                fee_vault,
+                &ctx.accounts.reward_mint,
+                ctx.accounts.reward_token_account.to_account_info(),
+                account.clone(),
+                reward_share,
+                hook_accounts,
+            )?;
+            let received = reward_share.saturating_sub(transfer_fee);
+
+            reward_distributed = reward_distributed
+                .checked_add(received)
+                .ok_or(FeeRoutingError::MathOverflow)?;
+            transfer_fees = transfer_fees
+                .checked_add(transfer_fee)
+                .ok_or(FeeRoutingError::MathOverflow)?;
+
+            msg!(
+                "Recipient {} ({} bps): Reward {} (transfer fee {})",
+                entry.recipient,
+                entry.weight_bps,
+                received,
+                transfer_fee
+            );
+        }
+
+        Ok((reward_distributed, transfer_fees))
+    }
+}
+
+/// Transfer `amount` out of a vault-owned token account with `transfer_checked`,
+/// signed by the fee vault PDA. Works for SPL Token and Token-2022 mints.
+///
+/// `hook_accounts` are the extra accounts for mints with a Token-2022 transfer
+/// hook; the token program picks out what the mint's `ExtraAccountMetaList`
+/// asks for and ignores the rest.
+///
+/// Returns the Token-2022 transfer fee withheld from `amount`, so the
+/// destination receives `amount` minus the returned fee.
+pub fn transfer_from_vault<'info>(
+    token_program: &Interface<'info, TokenInterface>,
+    fee_vault: &Account<'info, FeeVault>,
+    mint: &InterfaceAccount<'info, Mint>,
+    from: AccountInfo<'info>,
+    to: AccountInfo<'info>,
+    amount: u64,
+    hook_accounts: &[AccountInfo<'info>],
+) -> Result<u64> {
+    if amount == 0 {
+        return Ok(0);
+    }
+    let transfer_fee = TransferFees::on_send(&mint.to_account_info(), amount)?;
+
+    let seeds = fee_vault.signer_seeds();
+    let signer = &[&seeds[..]];
+
+    invoke_transfer_checked(
+        token_program.key,
+        from,
+        mint.to_account_info(),
+        to,
+        fee_vault.to_account_info(),
+        hook_accounts,
+        amount,
+        mint.decimals,
+        signer,
+    )?;
+
+    Ok(transfer_fee)
+}
+
+#[derive(Accounts)]
+pub struct DistributeToRecipients<'info> {
+    #[account(
+        seeds = [b"fee_vault", fee_vault.dlmm_pool.as_ref(), fee_vault.creator_seed()],
+        bump = fee_vault.bump
+    )]
+    pub fee_vault: Account<'info, FeeVault>,
+
+    #[account(
+        seeds = [b"distribution_config", fee_vault.key().as_ref()],
+        bump = distribution_config.bump,
+        has_one = fee_vault
+    )]
+    pub distribution_config: Account<'info, DistributionConfig>,
+
+    #[account(address = fee_vault.base_mint @ FeeRoutingError::InvalidDlmmMint)]
+    pub base_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(address = fee_vault.quote_mint @ FeeRoutingError::InvalidDlmmMint)]
+    pub quote_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        associated_token::mint = base_mint,
+        associated_token::authority = fee_vault,
+        associated_token::token_program = base_token_program
+    )]
+    pub base_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        associated_token::mint = quote_mint,
+        associated_token::authority = fee_vault,
+        associated_token::token_program = quote_token_program
+    )]
+    pub quote_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// Anyone may distribute; the caller only signs
+    pub caller: Signer<'info>,
+
+    #[account(address = fee_vault.base_token_program @ FeeRoutingError::InvalidTokenProgram)]
+    pub base_token_program: Interface<'info, TokenInterface>,
+
+    #[account(address = fee_vault.quote_token_program @ FeeRoutingError::InvalidTokenProgram)]
+    pub quote_token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+#[instruction(reward_index: u8)]
+pub struct DistributeRewardsToRecipients<'info> {
+    #[account(
+        seeds = [b"fee_vault", fee_vault.dlmm_pool.as_ref(), fee_vault.creator_seed()],
+        bump = fee_vault.bump
+    )]
+    pub fee_vault: Account<'info, FeeVault>,
+
+    #[account(
+        seeds = [b"distribution_config", fee_vault.key().as_ref()],
+        bump = distribution_config.bump,
+        has_one = fee_vault
+    )]
+    pub distribution_config: Account<'info, DistributionConfig>,
+
+    #[account(
+        constraint = fee_vault.reward_mints.get(reward_index as usize) == Some(&reward_mint.key()) @ FeeRoutingError::InvalidRewardIndex,
+        constraint = reward_mint.key() != fee_vault.base_mint && reward_mint.key() != fee_vault.quote_mint @ FeeRoutingError::RewardMintIsPoolMint,
+        mint::token_program = token_program
+    )]
+    pub reward_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        associated_token::mint = reward_mint,
+        associated_token::authority = fee_vault,
+        associated_token::token_program = token_program
+    )]
+    pub reward_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// Anyone may distribute; the caller only signs
+    pub caller: Signer<'info>,
+    /// SPL Token or Token-2022, whichever owns `reward_mint`
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+use crate::{DistributionConfig, FeeAmounts, FeeVault, FeeRoutingError, TransferFees};
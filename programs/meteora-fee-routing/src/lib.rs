@@ -1,12 +1,52 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{Token, TokenAccount, Mint};
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
 use anchor_spl::associated_token::AssociatedToken;
 
-mod dlmm_integration;
+pub mod dlmm_integration;
+mod config_timelock;
+mod events;
+mod fee_distribution;
 mod permissionless_interface;
+mod program_config;
+mod protocol_fee;
+mod recipient_claims;
+mod reward_collection;
+mod roles;
+mod transfer_fees;
+mod transfer_hooks;
+mod vault_closure;
+mod vault_migration;
+mod vault_multisig;
+mod vault_positions;
 
+use config_timelock::*;
 use dlmm_integration::*;
+use events::*;
+use fee_distribution::*;
 use permissionless_interface::*;
+use program_config::*;
+use protocol_fee::*;
+use recipient_claims::*;
+use reward_collection::*;
+use roles::*;
+use transfer_fees::*;
+use vault_closure::*;
+use vault_migration::*;
+use vault_multisig::*;
+use vault_positions::*;
+use dlmm_integration::meteora_dlmm_types::NUM_REWARDS;
+
+// Instruction arguments and limits, re-exported for off-chain clients
+pub use config_timelock::{ConfigChange, MAX_CONFIG_DELAY};
+pub use fee_distribution::{RecipientWeight, BPS_DENOMINATOR, MAX_RECIPIENTS};
+pub use permissionless_interface::MAX_POSITIONS_PER_BATCH;
+pub use reward_collection::ACCOUNTS_PER_REWARD;
+pub use program_config::{ProgramConfigParams, MAX_ALLOWED_DLMM_PROGRAMS, VAULTS_PER_PAGE};
+pub use protocol_fee::MAX_PROTOCOL_FEE_BPS;
+pub use roles::Role;
+pub use transfer_hooks::TransferHooks;
+pub use vault_migration::FeeVaultV0;
+pub use vault_multisig::MAX_MULTISIG_MEMBERS;
 
 declare_id!("FeeRtG9mEpMFEBPqhN5xjLrP4KdE5FGHxFpEhGkGKQW");
 
@@ -14,6 +54,50 @@ declare_id!("FeeRtG9mEpMFEBPqhN5xjLrP4KdE5FGHxFpEhGkGKQW");
 pub mod meteora_fee_routing {
     use super::*;
 
+    /// Create the program-wide config singleton (only the program upgrade authority)
+    pub fn initialize_program_config(
+        ctx: Context<InitializeProgramConfig>,
+        params: ProgramConfigParams,
+    ) -> Result<()> {
+        ProgramPolicy::validate(&params)?;
+
+        let program_config = &mut ctx.accounts.program_config;
+        program_config.bump = ctx.bumps.program_config;
+        program_config.vault_count = 0;
+        ProgramPolicy::apply(program_config, params);
+
+        emit!(ProgramConfigUpdated {
+            admin: program_config.admin,
+            protocol_fee_bps: program_config.protocol_fee_bps,
+            allowed_dlmm_programs: program_config.allowed_dlmm_programs.clone(),
+            default_config_delay: program_config.default_config_delay,
+            treasury: program_config.treasury,
+            slot: Clock::get()?.slot,
+        });
+        Ok(())
+    }
+
+    /// Change the program-wide policy, or hand it to a new admin (only program admin)
+    pub fn update_program_config(
+        ctx: Context<UpdateProgramConfig>,
+        params: ProgramConfigParams,
+    ) -> Result<()> {
+        ProgramPolicy::validate(&params)?;
+
+        let program_config = &mut ctx.accounts.program_config;
+        ProgramPolicy::apply(program_config, params);
+
+        emit!(ProgramConfigUpdated {
+            admin: program_config.admin,
+            protocol_fee_bps: program_config.protocol_fee_bps,
+            allowed_dlmm_programs: program_config.allowed_dlmm_programs.clone(),
+            default_config_delay: program_config.default_config_delay,
+            treasury: program_config.treasury,
+            slot: Clock::get()?.slot,
+        });
+        Ok(())
+    }
+
     /// Initialize the fee routing vault for a specific DLMM pool
     pub fn initialize_fee_vault(
         ctx: Context<InitializeFeeVault>,
@@ -24,126 +108,837 @@ pub mod meteora_fee_routing {
         fee_vault.dlmm_pool = ctx.accounts.dlmm_pool.key();
         fee_vault.base_mint = ctx.accounts.base_mint.key();
         fee_vault.quote_mint = ctx.accounts.quote_mint.key();
+        fee_vault.base_token_program = ctx.accounts.base_token_program.key();
+        fee_vault.quote_token_program = ctx.accounts.quote_token_program.key();
         fee_vault.bump = bump;
         fee_vault.total_collected_base = 0;
         fee_vault.total_collected_quote = 0;
         fee_vault.creation_time = Clock::get()?.unix_timestamp;
-        
-        msg!("Fee vault initialized for DLMM pool: {}", ctx.accounts.dlmm_pool.key());
+        fee_vault.total_shares = 0;
+        fee_vault.fee_per_share_base = 0;
+        fee_vault.fee_per_share_quote = 0;
+        fee_vault.reserved_base = 0;
+        fee_vault.reserved_quote = 0;
+        fee_vault.distributor = None;
+        fee_vault.total_protocol_fees_base = 0;
+        fee_vault.total_protocol_fees_quote = 0;
+        fee_vault.reward_mints = [Pubkey::default(); NUM_REWARDS];
+        fee_vault.total_collected_rewards = [0; NUM_REWARDS];
+        fee_vault.total_collected_gross_base = 0;
+        fee_vault.total_collected_gross_quote = 0;
+        fee_vault.pending_authority = None;
+        fee_vault.config_delay = ctx.accounts.program_config.default_config_delay;
+        fee_vault.next_change_id = 0;
+        fee_vault.paused = 0;
+        fee_vault.guardian = None;
+        fee_vault.config_manager = None;
+        fee_vault.total_skimmed_base = 0;
+        fee_vault.total_skimmed_quote = 0;
+        fee_vault.creator = ctx.accounts.authority.key();
+        fee_vault.version = FEE_VAULT_VERSION;
+        fee_vault.position_count = 0;
+        fee_vault.recipient_count = 0;
+        fee_vault.queued_change_count = 0;
+        fee_vault.reserved = [0; FEE_VAULT_RESERVED];
+
+        ProgramPolicy::register(
+            &mut ctx.accounts.program_config,
+            &mut ctx.accounts.vault_registry,
+            ctx.bumps.vault_registry,
+            fee_vault.key(),
+        )?;
+
+        emit!(VaultInitialized {
+            fee_vault: fee_vault.key(),
+            dlmm_pool: fee_vault.dlmm_pool,
+            authority: fee_vault.authority,
+            base_mint: fee_vault.base_mint,
+            quote_mint: fee_vault.quote_mint,
+            slot: Clock::get()?.slot,
+        });
         Ok(())
     }
 
     /// Collect fees from DLMM position (permissionless)
-    pub fn collect_position_fees(
-        ctx: Context<CollectPositionFees>,
+    /// `bin_array_count` is how many leading remaining accounts are the position's bin arrays.
+    pub fn collect_position_fees<'info>(
+        mut ctx: Context<'_, '_, 'info, 'info, CollectPositionFees<'info>>,
+        bin_array_count: u8,
     ) -> Result<()> {
-        let (base_collected, quote_collected) = PermissionlessInterface::collect_position_fees(&ctx)?;
-        
-        let fee_vault = &mut ctx.accounts.fee_vault;
-        fee_vault.total_collected_base = fee_vault.total_collected_base
-            .checked_add(base_collected)
+        ctx.accounts.fee_vault.require_not_paused(PAUSE_COLLECT)?;
+        let collected = PermissionlessInterface::collect_position_fees(&mut ctx, bin_array_count)?;
+
+        // Already checked to fit by the bin array split
+        let hook_accounts = &ctx.remaining_accounts[bin_array_count as usize..];
+        let accounts = &mut ctx.accounts;
+        let (routable_base, routable_quote) = ProtocolFee::skim(
+            &mut accounts.fee_vault,
+            &accounts.program_config,
+            &collected,
+            (&accounts.base_token_account, &accounts.quote_token_account),
+            (&accounts.treasury_base_account, &accounts.treasury_quote_account),
+            (&accounts.base_mint, &accounts.quote_mint),
+            (&accounts.base_token_program, &accounts.quote_token_program),
+            hook_accounts,
+        )?;
+
+        let fee_vault = &mut accounts.fee_vault;
+        fee_vault.record_fees(&collected)?;
+        PullClaims::accrue(fee_vault, routable_base, routable_quote)?;
+
+        emit!(FeesCollected {
+            fee_vault: fee_vault.key(),
+            dlmm_pool: fee_vault.dlmm_pool,
+            positions: 1,
+            base_amount: collected.base,
+            quote_amount: collected.quote,
+            base_transfer_fee: collected.base_transfer_fee,
+            quote_transfer_fee: collected.quote_transfer_fee,
+            total_collected_base: fee_vault.total_collected_base,
+            total_collected_quote: fee_vault.total_collected_quote,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    /// Collect fees from several positions of the same pool in one transaction.
+    /// `bin_array_counts` holds how many bin arrays follow each position in the remaining accounts.
+    pub fn collect_many_position_fees<'info>(
+        mut ctx: Context<'_, '_, 'info, 'info, CollectManyPositionFees<'info>>,
+        bin_array_counts: Vec<u8>,
+    ) -> Result<()> {
+        ctx.accounts.fee_vault.require_not_paused(PAUSE_COLLECT)?;
+        let (collected, positions) =
+            PermissionlessInterface::collect_many_position_fees(&mut ctx, &bin_array_counts)?;
+
+        // Already checked to fit by the batch split
+        let hook_accounts = &ctx.remaining_accounts[PermissionlessInterface::batch_len(&bin_array_counts)..];
+        let accounts = &mut ctx.accounts;
+        let (routable_base, routable_quote) = ProtocolFee::skim(
+            &mut accounts.fee_vault,
+            &accounts.program_config,
+            &collected,
+            (&accounts.base_token_account, &accounts.quote_token_account),
+            (&accounts.treasury_base_account, &accounts.treasury_quote_account),
+            (&accounts.base_mint, &accounts.quote_mint),
+            (&accounts.base_token_program, &accounts.quote_token_program),
+            hook_accounts,
+        )?;
+
+        let fee_vault = &mut accounts.fee_vault;
+        fee_vault.record_fees(&collected)?;
+        PullClaims::accrue(fee_vault, routable_base, routable_quote)?;
+
+        emit!(FeesCollected {
+            fee_vault: fee_vault.key(),
+            dlmm_pool: fee_vault.dlmm_pool,
+            positions: positions as u8,
+            base_amount: collected.base,
+            quote_amount: collected.quote,
+            base_transfer_fee: collected.base_transfer_fee,
+            quote_transfer_fee: collected.quote_transfer_fee,
+            total_collected_base: fee_vault.total_collected_base,
+            total_collected_quote: fee_vault.total_collected_quote,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    /// Claim the pool's protocol fees into the vault (permissionless, vault must be a DLMM claim fee operator)
+    /// `remaining_accounts` carries the transfer hook accounts of hooked mints, used by the DLMM claim and the protocol fee skim.
+    pub fn collect_protocol_fees<'info>(
+        mut ctx: Context<'_, '_, 'info, 'info, CollectProtocolFees<'info>>,
+    ) -> Result<()> {
+        ctx.accounts.fee_vault.require_not_paused(PAUSE_COLLECT)?;
+        let collected = PermissionlessInterface::collect_protocol_fees(&mut ctx)?;
+
+        let accounts = &mut ctx.accounts;
+        let (routable_base, routable_quote) = ProtocolFee::skim(
+            &mut accounts.fee_vault,
+            &accounts.program_config,
+            &collected,
+            (&accounts.base_token_account, &accounts.quote_token_account),
+            (&accounts.treasury_base_account, &accounts.treasury_quote_account),
+            (&accounts.base_mint, &accounts.quote_mint),
+            (&accounts.base_token_program, &accounts.quote_token_program),
+            ctx.remaining_accounts,
+        )?;
+
+        let fee_vault = &mut accounts.fee_vault;
+        fee_vault.total_protocol_fees_base = fee_vault.total_protocol_fees_base
+            .checked_add(collected.base)
             .ok_or(FeeRoutingError::MathOverflow)?;
-        fee_vault.total_collected_quote = fee_vault.total_collected_quote
-            .checked_add(quote_collected)
+        fee_vault.total_protocol_fees_quote = fee_vault.total_protocol_fees_quote
+            .checked_add(collected.quote)
             .ok_or(FeeRoutingError::MathOverflow)?;
+        PullClaims::accrue(fee_vault, routable_base, routable_quote)?;
+
+        emit!(ProtocolFeesCollected {
+            fee_vault: fee_vault.key(),
+            dlmm_pool: fee_vault.dlmm_pool,
+            base_amount: collected.base,
+            quote_amount: collected.quote,
+            base_transfer_fee: collected.base_transfer_fee,
+            quote_transfer_fee: collected.quote_transfer_fee,
+            total_protocol_fees_base: fee_vault.total_protocol_fees_base,
+            total_protocol_fees_quote: fee_vault.total_protocol_fees_quote,
+            slot: Clock::get()?.slot,
+        });
 
-        msg!(
-            "Position fees collected - Base: {}, Quote: {}", 
-            base_collected, 
-            quote_collected
-        );
-        
         Ok(())
     }
 
-    /// Distribute collected fees to specified recipients
-    pub fn distribute_fees(
-        ctx: Context<DistributeFees>,
+    /// Claim the pool's farming rewards for a position into vault-owned ATAs (permissionless)
+    /// `bin_array_count` is how many leading remaining accounts are the position's bin arrays.
+    pub fn collect_position_rewards<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CollectPositionRewards<'info>>,
+        bin_array_count: u8,
+    ) -> Result<()> {
+        ctx.accounts.fee_vault.require_not_paused(PAUSE_COLLECT)?;
+        let collected = RewardCollection::collect(&ctx, bin_array_count)?;
+
+        let slot = Clock::get()?.slot;
+        let fee_vault = &mut ctx.accounts.fee_vault;
+        for (index, mint, amount, transfer_fee) in collected {
+            fee_vault.record_reward(index, mint, amount)?;
+
+            emit!(RewardCollected {
+                fee_vault: fee_vault.key(),
+                dlmm_pool: fee_vault.dlmm_pool,
+                reward_index: index as u8,
+                reward_mint: mint,
+                amount,
+                transfer_fee,
+                total_collected: fee_vault.total_collected_rewards[index],
+                slot,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Distribute collected fees to specified recipients.
+    ///
+    /// The amounts are what the recipient receives. For Token-2022 mints with a
+    /// transfer fee the vault sends enough on top to cover the withheld fee.
+    /// Transfer hook accounts for hooked mints go in `remaining_accounts`,
+    /// followed by the approving members when the distributor is a multisig.
+    pub fn distribute_fees<'info>(
+        ctx: Context<'_, '_, 'info, 'info, DistributeFees<'info>>,
         base_amount: u64,
         quote_amount: u64,
     ) -> Result<()> {
+        MultisigApproval::authorize(&ctx.accounts.authority, &ctx.accounts.fee_vault.key(), ctx.remaining_accounts)?;
+        ctx.accounts.fee_vault.require_not_paused(PAUSE_DISTRIBUTE)?;
+
         let fee_vault = &mut ctx.accounts.fee_vault;
+        let base_gross = TransferFees::gross_for(&ctx.accounts.base_mint.to_account_info(), base_amount)?;
+        let quote_gross = TransferFees::gross_for(&ctx.accounts.quote_mint.to_account_info(), quote_amount)?;
         
-        // Validate sufficient balance
+        // Validate sufficient balance, excluding fees already owed to recipient positions
         require!(
-            ctx.accounts.base_token_account.amount >= base_amount,
+            ctx.accounts.base_token_account.amount.saturating_sub(fee_vault.reserved_base) >= base_gross,
             FeeRoutingError::InsufficientBalance
         );
         require!(
-            ctx.accounts.quote_token_account.amount >= quote_amount,
+            ctx.accounts.quote_token_account.amount.saturating_sub(fee_vault.reserved_quote) >= quote_gross,
             FeeRoutingError::InsufficientBalance
         );
 
         // Transfer tokens to recipient
-        if base_amount > 0 {
-            let transfer_instruction = anchor_spl::token::Transfer {
-                from: ctx.accounts.base_token_account.to_account_info(),
-                to: ctx.accounts.recipient_base_account.to_account_info(),
-                authority: fee_vault.to_account_info(),
-            };
-            
-            let seeds = &[
-                b"fee_vault",
-                fee_vault.dlmm_pool.as_ref(),
-                &[fee_vault.bump],
-            ];
-            let signer = &[&seeds[..]];
-            
-            anchor_spl::token::transfer(
-                CpiContext::new_with_signer(
-                    ctx.accounts.token_program.to_account_info(),
-                    transfer_instruction,
-                    signer,
-                ),
-                base_amount,
-            )?;
-        }
+        let base_transfer_fee = transfer_from_vault(
+            &ctx.accounts.base_token_program,
+            fee_vault,
+            &ctx.accounts.base_mint,
+            ctx.accounts.base_token_account.to_account_info(),
+            ctx.accounts.recipient_base_account.to_account_info(),
+            base_gross,
+            ctx.remaining_accounts,
+        )?;
+        let quote_transfer_fee = transfer_from_vault(
+            &ctx.accounts.quote_token_program,
+            fee_vault,
+            &ctx.accounts.quote_mint,
+            ctx.accounts.quote_token_account.to_account_info(),
+            ctx.accounts.recipient_quote_account.to_account_info(),
+            quote_gross,
+            ctx.remaining_accounts,
+        )?;
+
+        emit!(FeesDistributed {
+            fee_vault: fee_vault.key(),
+            dlmm_pool: fee_vault.dlmm_pool,
+            recipient: ctx.accounts.recipient.key(),
+            base_amount: base_gross.saturating_sub(base_transfer_fee),
+            quote_amount: quote_gross.saturating_sub(quote_transfer_fee),
+            base_transfer_fee,
+            quote_transfer_fee,
+            slot: Clock::get()?.slot,
+        });
 
-        if quote_amount > 0 {
-            let transfer_instruction = anchor_spl::token::Transfer {
-                from: ctx.accounts.quote_token_account.to_account_info(),
-                to: ctx.accounts.recipient_quote_account.to_account_info(),
-                authority: fee_vault.to_account_info(),
-            };
-            
-            let seeds = &[
-                b"fee_vault",
-                fee_vault.dlmm_pool.as_ref(),
-                &[fee_vault.bump],
-            ];
-            let signer = &[&seeds[..]];
-            
-            anchor_spl::token::transfer(
-                CpiContext::new_with_signer(
-                    ctx.accounts.token_program.to_account_info(),
-                    transfer_instruction,
-                    signer,
-                ),
-                quote_amount,
-            )?;
+        Ok(())
+    }
+
+    /// Create the weighted recipient split table for a fee vault (authority or config manager)
+    pub fn initialize_distribution_config(
+        ctx: Context<InitializeDistributionConfig>,
+        recipients: Vec<RecipientWeight>,
+    ) -> Result<()> {
+        MultisigApproval::authorize(&ctx.accounts.authority, &ctx.accounts.fee_vault.key(), ctx.remaining_accounts)?;
+        ctx.accounts.fee_vault.require_untimelocked()?;
+        WeightedDistribution::validate_recipients(&recipients)?;
+
+        let distribution_config = &mut ctx.accounts.distribution_config;
+        distribution_config.fee_vault = ctx.accounts.fee_vault.key();
+        distribution_config.bump = ctx.bumps.distribution_config;
+        distribution_config.recipients = recipients;
+
+        emit!(DistributionConfigUpdated {
+            fee_vault: distribution_config.fee_vault,
+            dlmm_pool: ctx.accounts.fee_vault.dlmm_pool,
+            recipients: distribution_config.recipients.clone(),
+            slot: Clock::get()?.slot,
+        });
+        Ok(())
+    }
+
+    /// Replace the weighted recipient split table (authority or config manager)
+    pub fn update_distribution_config(
+        ctx: Context<UpdateDistributionConfig>,
+        recipients: Vec<RecipientWeight>,
+    ) -> Result<()> {
+        MultisigApproval::authorize(&ctx.accounts.authority, &ctx.accounts.fee_vault.key(), ctx.remaining_accounts)?;
+        ctx.accounts.fee_vault.require_untimelocked()?;
+        WeightedDistribution::validate_recipients(&recipients)?;
+
+        let distribution_config = &mut ctx.accounts.distribution_config;
+        distribution_config.recipients = recipients;
+
+        emit!(DistributionConfigUpdated {
+            fee_vault: distribution_config.fee_vault,
+            dlmm_pool: ctx.accounts.fee_vault.dlmm_pool,
+            recipients: distribution_config.recipients.clone(),
+            slot: Clock::get()?.slot,
+        });
+        Ok(())
+    }
+
+    /// Split the vault's current balances across all configured recipients
+    pub fn distribute_to_recipients<'info>(
+        ctx: Context<'_, '_, 'info, 'info, DistributeToRecipients<'info>>,
+    ) -> Result<()> {
+        ctx.accounts.fee_vault.require_not_paused(PAUSE_DISTRIBUTE)?;
+        let distributed = WeightedDistribution::distribute(&ctx)?;
+
+        emit!(RecipientsDistributed {
+            fee_vault: ctx.accounts.fee_vault.key(),
+            dlmm_pool: ctx.accounts.fee_vault.dlmm_pool,
+            recipients: ctx.accounts.distribution_config.recipients.len() as u8,
+            base_amount: distributed.base,
+            quote_amount: distributed.quote,
+            base_transfer_fee: distributed.base_transfer_fee,
+            quote_transfer_fee: distributed.quote_transfer_fee,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    /// Split a collected farming reward across all configured recipients
+    pub fn distribute_rewards_to_recipients<'info>(
+        ctx: Context<'_, '_, 'info, 'info, DistributeRewardsToRecipients<'info>>,
+        reward_index: u8,
+    ) -> Result<()> {
+        ctx.accounts.fee_vault.require_not_paused(PAUSE_DISTRIBUTE)?;
+        let (reward_distributed, transfer_fee) = WeightedDistribution::distribute_reward(&ctx)?;
+
+        emit!(RewardDistributed {
+            fee_vault: ctx.accounts.fee_vault.key(),
+            dlmm_pool: ctx.accounts.fee_vault.dlmm_pool,
+            reward_index,
+            reward_mint: ctx.accounts.reward_mint.key(),
+            recipients: ctx.accounts.distribution_config.recipients.len() as u8,
+            amount: reward_distributed,
+            transfer_fee,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    /// Open a pull-based claim position for a recipient (authority or config manager). While the
+    /// vault has a config delay the position opens with zero shares.
+    pub fn open_recipient_position(
+        ctx: Context<OpenRecipientPosition>,
+        shares: u64,
+    ) -> Result<()> {
+        MultisigApproval::authorize(&ctx.accounts.authority, &ctx.accounts.fee_vault.key(), ctx.remaining_accounts)?;
+        if ctx.accounts.fee_vault.config_delay == 0 {
+            require!(shares > 0, FeeRoutingError::InvalidShares);
+        } else {
+            // Shares for positions opened under a delay are granted by a queued change
+            require!(shares == 0, FeeRoutingError::ConfigTimelocked);
         }
 
-        msg!(
-            "Distributed fees to {}: Base {}, Quote {}",
-            ctx.accounts.recipient.key(),
-            base_amount,
-            quote_amount
-        );
+        let fee_vault = &mut ctx.accounts.fee_vault;
+        let recipient_position = &mut ctx.accounts.recipient_position;
+        recipient_position.fee_vault = fee_vault.key();
+        recipient_position.recipient = ctx.accounts.recipient.key();
+        recipient_position.bump = ctx.bumps.recipient_position;
+        recipient_position.shares = shares;
+        recipient_position.fee_per_share_base_checkpoint = fee_vault.fee_per_share_base;
+        recipient_position.fee_per_share_quote_checkpoint = fee_vault.fee_per_share_quote;
+        recipient_position.pending_base = 0;
+        recipient_position.pending_quote = 0;
+        recipient_position.total_claimed_base = 0;
+        recipient_position.total_claimed_quote = 0;
+
+        fee_vault.total_shares = fee_vault.total_shares
+            .checked_add(shares)
+            .ok_or(FeeRoutingError::MathOverflow)?;
+        fee_vault.recipient_count = fee_vault.recipient_count
+            .checked_add(1)
+            .ok_or(FeeRoutingError::MathOverflow)?;
+
+        emit!(RecipientSharesUpdated {
+            fee_vault: fee_vault.key(),
+            dlmm_pool: fee_vault.dlmm_pool,
+            recipient: recipient_position.recipient,
+            shares,
+            total_shares: fee_vault.total_shares,
+            slot: Clock::get()?.slot,
+        });
+        Ok(())
+    }
+
+    /// Change a recipient's share count, settling what it has accrued so far (authority or config manager)
+    pub fn update_recipient_shares(
+        ctx: Context<UpdateRecipientShares>,
+        shares: u64,
+    ) -> Result<()> {
+        MultisigApproval::authorize(&ctx.accounts.authority, &ctx.accounts.fee_vault.key(), ctx.remaining_accounts)?;
+        ctx.accounts.fee_vault.require_untimelocked()?;
+
+        let fee_vault = &mut ctx.accounts.fee_vault;
+        let recipient_position = &mut ctx.accounts.recipient_position;
+
+        PullClaims::set_shares(fee_vault, recipient_position, shares)?;
+
+        emit!(RecipientSharesUpdated {
+            fee_vault: fee_vault.key(),
+            dlmm_pool: fee_vault.dlmm_pool,
+            recipient: recipient_position.recipient,
+            shares,
+            total_shares: fee_vault.total_shares,
+            slot: Clock::get()?.slot,
+        });
+        Ok(())
+    }
+
+    /// Close a recipient position that has claimed everything it accrued, sending its rent to
+    /// `rent_receiver` (authority or config manager). Closing the last one hands the rounding
+    /// dust left in the reserve back to the routable balance.
+    pub fn close_recipient_position(ctx: Context<CloseRecipientPosition>) -> Result<()> {
+        MultisigApproval::authorize(&ctx.accounts.authority, &ctx.accounts.fee_vault.key(), ctx.remaining_accounts)?;
+        ctx.accounts.fee_vault.require_untimelocked()?;
+
+        let fee_vault = &mut ctx.accounts.fee_vault;
+        PullClaims::close(fee_vault, &mut ctx.accounts.recipient_position)?;
+
+        emit!(RecipientPositionClosed {
+            fee_vault: fee_vault.key(),
+            dlmm_pool: fee_vault.dlmm_pool,
+            recipient: ctx.accounts.recipient_position.recipient,
+            total_shares: fee_vault.total_shares,
+            rent_receiver: ctx.accounts.rent_receiver.key(),
+            slot: Clock::get()?.slot,
+        });
+        Ok(())
+    }
+
+    /// Pay a recipient everything its position has accrued (permissionless)
+    pub fn claim<'info>(ctx: Context<'_, '_, 'info, 'info, Claim<'info>>) -> Result<()> {
+        ctx.accounts.fee_vault.require_not_paused(PAUSE_CLAIM)?;
+        let claimed = PullClaims::claim(ctx.accounts, ctx.remaining_accounts)?;
+
+        emit!(FeesClaimed {
+            fee_vault: ctx.accounts.fee_vault.key(),
+            dlmm_pool: ctx.accounts.fee_vault.dlmm_pool,
+            recipient: ctx.accounts.recipient.key(),
+            base_amount: claimed.base,
+            quote_amount: claimed.quote,
+            base_transfer_fee: claimed.base_transfer_fee,
+            quote_transfer_fee: claimed.quote_transfer_fee,
+            total_claimed_base: ctx.accounts.recipient_position.total_claimed_base,
+            total_claimed_quote: ctx.accounts.recipient_position.total_claimed_quote,
+            slot: Clock::get()?.slot,
+        });
+        Ok(())
+    }
+
+    /// Propose a new vault authority, who has to accept before it takes over (only authority)
+    pub fn propose_authority(
+        ctx: Context<UpdateFeeVault>,
+        new_authority: Pubkey,
+    ) -> Result<()> {
+        MultisigApproval::authorize(&ctx.accounts.authority, &ctx.accounts.fee_vault.key(), ctx.remaining_accounts)?;
+        ctx.accounts.fee_vault.require_untimelocked()?;
+
+        let fee_vault = &mut ctx.accounts.fee_vault;
+        fee_vault.pending_authority = Some(new_authority);
+
+        emit!(AuthorityProposed {
+            fee_vault: fee_vault.key(),
+            dlmm_pool: fee_vault.dlmm_pool,
+            authority: fee_vault.authority,
+            pending_authority: new_authority,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    /// Take over as vault authority (only the pending authority)
+    pub fn accept_authority(ctx: Context<AcceptAuthority>) -> Result<()> {
+        MultisigApproval::authorize(&ctx.accounts.pending_authority, &ctx.accounts.fee_vault.key(), ctx.remaining_accounts)?;
+        let fee_vault = &mut ctx.accounts.fee_vault;
+        let old_authority = fee_vault.authority;
+        fee_vault.authority = ctx.accounts.pending_authority.key();
+        fee_vault.pending_authority = None;
+
+        emit!(AuthorityChanged {
+            fee_vault: fee_vault.key(),
+            dlmm_pool: fee_vault.dlmm_pool,
+            old_authority,
+            new_authority: fee_vault.authority,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    /// Withdraw a proposed authority transfer before it is accepted (only authority)
+    pub fn cancel_authority_transfer(ctx: Context<UpdateFeeVault>) -> Result<()> {
+        MultisigApproval::authorize(&ctx.accounts.authority, &ctx.accounts.fee_vault.key(), ctx.remaining_accounts)?;
+        let fee_vault = &mut ctx.accounts.fee_vault;
+        let pending_authority = fee_vault.pending_authority
+            .take()
+            .ok_or(FeeRoutingError::NoPendingAuthority)?;
+
+        emit!(AuthorityTransferCancelled {
+            fee_vault: fee_vault.key(),
+            dlmm_pool: fee_vault.dlmm_pool,
+            authority: fee_vault.authority,
+            pending_authority,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    /// Pause or resume collection, distribution and claims. `flags` replaces the
+    /// current set (authority or guardian)
+    pub fn set_pause_flags(
+        ctx: Context<SetPauseFlags>,
+        flags: u8,
+    ) -> Result<()> {
+        MultisigApproval::authorize(&ctx.accounts.authority, &ctx.accounts.fee_vault.key(), ctx.remaining_accounts)?;
+        require!(flags & !PAUSE_ALL == 0, FeeRoutingError::InvalidPauseFlags);
+
+        let fee_vault = &mut ctx.accounts.fee_vault;
+        let old_flags = fee_vault.paused;
+        fee_vault.paused = flags;
+
+        emit!(PauseFlagsChanged {
+            fee_vault: fee_vault.key(),
+            dlmm_pool: fee_vault.dlmm_pool,
+            old_flags,
+            new_flags: flags,
+            authority: ctx.accounts.authority.key(),
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    /// Hand a delegated role to `holder` (only authority)
+    pub fn grant_role(
+        ctx: Context<UpdateFeeVault>,
+        role: Role,
+        holder: Pubkey,
+    ) -> Result<()> {
+        MultisigApproval::authorize(&ctx.accounts.authority, &ctx.accounts.fee_vault.key(), ctx.remaining_accounts)?;
+        ctx.accounts.fee_vault.require_untimelocked()?;
+        VaultRoles::assign(&mut ctx.accounts.fee_vault, role, Some(holder))
+    }
+
+    /// Take a delegated role away from whoever holds it (only authority)
+    pub fn revoke_role(
+        ctx: Context<UpdateFeeVault>,
+        role: Role,
+    ) -> Result<()> {
+        MultisigApproval::authorize(&ctx.accounts.authority, &ctx.accounts.fee_vault.key(), ctx.remaining_accounts)?;
+        ctx.accounts.fee_vault.require_untimelocked()?;
+        VaultRoles::assign(&mut ctx.accounts.fee_vault, role, None)
+    }
+
+    /// Create the vault's M-of-N signer set. It holds no role until the authority
+    /// hands one over, e.g. with `propose_authority` (only authority)
+    pub fn create_vault_multisig(
+        ctx: Context<CreateVaultMultisig>,
+        members: Vec<Pubkey>,
+        threshold: u8,
+    ) -> Result<()> {
+        MultisigApproval::authorize(&ctx.accounts.authority, &ctx.accounts.fee_vault.key(), ctx.remaining_accounts)?;
+        MultisigApproval::validate(&members, threshold)?;
+
+        let vault_multisig = &mut ctx.accounts.vault_multisig;
+        vault_multisig.fee_vault = ctx.accounts.fee_vault.key();
+        vault_multisig.bump = ctx.bumps.vault_multisig;
+        vault_multisig.threshold = threshold;
+        vault_multisig.members = members;
+
+        emit!(MultisigUpdated {
+            fee_vault: vault_multisig.fee_vault,
+            dlmm_pool: ctx.accounts.fee_vault.dlmm_pool,
+            multisig: vault_multisig.key(),
+            members: vault_multisig.members.clone(),
+            threshold,
+            slot: Clock::get()?.slot,
+        });
 
         Ok(())
     }
 
-    /// Update fee vault configuration (only authority)
-    pub fn update_fee_vault(
+    /// Replace the multisig's members and threshold (the multisig itself). Once
+    /// a config delay is set this goes through the queue as `ConfigChange::Multisig`
+    pub fn update_vault_multisig(
+        ctx: Context<UpdateVaultMultisig>,
+        members: Vec<Pubkey>,
+        threshold: u8,
+    ) -> Result<()> {
+        MultisigApproval::authorize(
+            &ctx.accounts.vault_multisig.to_account_info(),
+            &ctx.accounts.fee_vault.key(),
+            ctx.remaining_accounts,
+        )?;
+        ctx.accounts.fee_vault.require_untimelocked()?;
+        MultisigApproval::validate(&members, threshold)?;
+
+        let vault_multisig = &mut ctx.accounts.vault_multisig;
+        vault_multisig.threshold = threshold;
+        vault_multisig.members = members;
+
+        emit!(MultisigUpdated {
+            fee_vault: vault_multisig.fee_vault,
+            dlmm_pool: ctx.accounts.fee_vault.dlmm_pool,
+            multisig: vault_multisig.key(),
+            members: vault_multisig.members.clone(),
+            threshold,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    /// Turn on the configuration timelock. Once set, the delay itself can only be
+    /// changed through the queue (only authority)
+    pub fn set_config_delay(
         ctx: Context<UpdateFeeVault>,
-        new_authority: Option<Pubkey>,
+        delay: i64,
     ) -> Result<()> {
+        MultisigApproval::authorize(&ctx.accounts.authority, &ctx.accounts.fee_vault.key(), ctx.remaining_accounts)?;
+        ctx.accounts.fee_vault.require_untimelocked()?;
+        ConfigTimelock::validate_delay(delay)?;
+
         let fee_vault = &mut ctx.accounts.fee_vault;
-        
-        if let Some(new_auth) = new_authority {
-            fee_vault.authority = new_auth;
-            msg!("Fee vault authority updated to: {}", new_auth);
-        }
+        let old_delay = fee_vault.config_delay;
+        fee_vault.config_delay = delay;
+
+        emit!(ConfigDelayChanged {
+            fee_vault: fee_vault.key(),
+            dlmm_pool: fee_vault.dlmm_pool,
+            old_delay,
+            new_delay: delay,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    /// Schedule a configuration change for `now + config_delay`. Recipient changes can be
+    /// queued by the config manager, everything else only by the authority
+    pub fn queue_config_change(
+        ctx: Context<QueueConfigChange>,
+        change: ConfigChange,
+    ) -> Result<()> {
+        MultisigApproval::authorize(&ctx.accounts.authority, &ctx.accounts.fee_vault.key(), ctx.remaining_accounts)?;
+        ConfigTimelock::validate(&change)?;
+
+        let fee_vault = &mut ctx.accounts.fee_vault;
+        let eta = Clock::get()?.unix_timestamp
+            .checked_add(fee_vault.config_delay)
+            .ok_or(FeeRoutingError::MathOverflow)?;
+
+        let queued_change = &mut ctx.accounts.queued_change;
+        queued_change.fee_vault = fee_vault.key();
+        queued_change.id = fee_vault.next_change_id;
+        queued_change.payer = ctx.accounts.payer.key();
+        queued_change.bump = ctx.bumps.queued_change;
+        queued_change.eta = eta;
+        queued_change.change = change;
+
+        fee_vault.next_change_id = fee_vault.next_change_id
+            .checked_add(1)
+            .ok_or(FeeRoutingError::MathOverflow)?;
+        fee_vault.queued_change_count = fee_vault.queued_change_count
+            .checked_add(1)
+            .ok_or(FeeRoutingError::MathOverflow)?;
+
+        emit!(ConfigChangeQueued {
+            fee_vault: fee_vault.key(),
+            dlmm_pool: fee_vault.dlmm_pool,
+            id: queued_change.id,
+            change: queued_change.change.clone(),
+            eta,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    /// Apply a queued configuration change once its ETA has passed (permissionless)
+    pub fn execute_config_change(ctx: Context<ExecuteConfigChange>) -> Result<()> {
+        let queued_change = &ctx.accounts.queued_change;
+        require!(
+            Clock::get()?.unix_timestamp >= queued_change.eta,
+            FeeRoutingError::TimelockNotElapsed
+        );
+        let id = queued_change.id;
+        let change = queued_change.change.clone();
+
+        ConfigTimelock::apply(ctx.accounts, change)?;
+        ConfigTimelock::dequeue(&mut ctx.accounts.fee_vault)?;
+
+        emit!(ConfigChangeExecuted {
+            fee_vault: ctx.accounts.fee_vault.key(),
+            dlmm_pool: ctx.accounts.fee_vault.dlmm_pool,
+            id,
+            executor: ctx.accounts.executor.key(),
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    /// Drop a queued configuration change before it is executed (whoever could have queued it)
+    pub fn cancel_config_change(ctx: Context<CancelConfigChange>) -> Result<()> {
+        MultisigApproval::authorize(&ctx.accounts.authority, &ctx.accounts.fee_vault.key(), ctx.remaining_accounts)?;
+        ConfigTimelock::dequeue(&mut ctx.accounts.fee_vault)?;
+
+        emit!(ConfigChangeCancelled {
+            fee_vault: ctx.accounts.fee_vault.key(),
+            dlmm_pool: ctx.accounts.fee_vault.dlmm_pool,
+            id: ctx.accounts.queued_change.id,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    /// Realloc a v0 fee vault to the current layout in place, keeping its
+    /// `[b"fee_vault", dlmm_pool]` address, signed by its authority
+    pub fn migrate_fee_vault(ctx: Context<MigrateFeeVault>) -> Result<()> {
+        let fee_vault = FeeVaultMigration::migrate(ctx.accounts)?;
+
+        ProgramPolicy::register(
+            &mut ctx.accounts.program_config,
+            &mut ctx.accounts.vault_registry,
+            ctx.bumps.vault_registry,
+            ctx.accounts.fee_vault.key(),
+        )?;
+
+        emit!(FeeVaultMigrated {
+            fee_vault: ctx.accounts.fee_vault.key(),
+            dlmm_pool: fee_vault.dlmm_pool,
+            old_version: 0,
+            new_version: fee_vault.version,
+            payer: ctx.accounts.payer.key(),
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    /// Open a DLMM position owned by the vault and register it with the vault (only authority)
+    pub fn open_vault_position(
+        ctx: Context<OpenVaultPosition>,
+        lower_bin_id: i32,
+        width: i32,
+    ) -> Result<()> {
+        MultisigApproval::authorize(&ctx.accounts.authority, &ctx.accounts.fee_vault.key(), ctx.remaining_accounts)?;
+        VaultPositions::open(ctx.accounts, ctx.bumps.vault_position, lower_bin_id, width)?;
+
+        emit!(VaultPositionRegistered {
+            fee_vault: ctx.accounts.fee_vault.key(),
+            dlmm_pool: ctx.accounts.fee_vault.dlmm_pool,
+            position: ctx.accounts.position.key(),
+            position_count: ctx.accounts.fee_vault.position_count,
+            slot: Clock::get()?.slot,
+        });
+        Ok(())
+    }
+
+    /// Record a DLMM position the vault owns, so fees can be collected from it (permissionless)
+    pub fn register_vault_position(ctx: Context<RegisterVaultPosition>) -> Result<()> {
+        VaultPositions::register(ctx.accounts, ctx.bumps.vault_position)?;
+
+        emit!(VaultPositionRegistered {
+            fee_vault: ctx.accounts.fee_vault.key(),
+            dlmm_pool: ctx.accounts.fee_vault.dlmm_pool,
+            position: ctx.accounts.position.key(),
+            position_count: ctx.accounts.fee_vault.position_count,
+            slot: Clock::get()?.slot,
+        });
+        Ok(())
+    }
+
+    /// Drop a position that DLMM has closed or that holds no liquidity, returning the
+    /// registration's rent to its payer (only authority)
+    pub fn deregister_vault_position(ctx: Context<DeregisterVaultPosition>) -> Result<()> {
+        MultisigApproval::authorize(&ctx.accounts.authority, &ctx.accounts.fee_vault.key(), ctx.remaining_accounts)?;
+        VaultPositions::deregister(ctx.accounts)?;
+
+        emit!(VaultPositionDeregistered {
+            fee_vault: ctx.accounts.fee_vault.key(),
+            dlmm_pool: ctx.accounts.fee_vault.dlmm_pool,
+            position: ctx.accounts.position.key(),
+            position_count: ctx.accounts.fee_vault.position_count,
+            slot: Clock::get()?.slot,
+        });
+        Ok(())
+    }
+
+    /// Close an emptied vault with its token accounts, distribution config and multisig,
+    /// returning their rent, and take it off its registry page (only authority). Registered
+    /// positions, recipient positions and queued changes have to be closed first.
+    pub fn close_fee_vault<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CloseFeeVault<'info>>,
+    ) -> Result<()> {
+        MultisigApproval::authorize(&ctx.accounts.authority, &ctx.accounts.fee_vault.key(), ctx.remaining_accounts)?;
+        ctx.accounts.fee_vault.require_untimelocked()?;
+
+        VaultClosure::close(ctx.accounts)?;
+
+        emit!(VaultClosed {
+            fee_vault: ctx.accounts.fee_vault.key(),
+            dlmm_pool: ctx.accounts.fee_vault.dlmm_pool,
+            authority: ctx.accounts.authority.key(),
+            rent_receiver: ctx.accounts.rent_receiver.key(),
+            slot: Clock::get()?.slot,
+        });
 
         Ok(())
     }
@@ -156,7 +951,7 @@ pub struct InitializeFeeVault<'info> {
         init,
         payer = authority,
         space = FeeVault::LEN,
-        seeds = [b"fee_vault", dlmm_pool.key().as_ref()],
+        seeds = [b"fee_vault", dlmm_pool.key().as_ref(), authority.key().as_ref()],
         bump
     )]
     pub fee_vault: Account<'info, FeeVault>,
@@ -164,115 +959,260 @@ pub struct InitializeFeeVault<'info> {
     /// CHECK: This is the DLMM pool account
     pub dlmm_pool: UncheckedAccount<'info>,
     
-    pub base_mint: Account<'info, Mint>,
-    pub quote_mint: Account<'info, Mint>,
+    #[account(mint::token_program = base_token_program)]
+    pub base_mint: InterfaceAccount<'info, Mint>,
+    #[account(mint::token_program = quote_token_program)]
+    pub quote_mint: InterfaceAccount<'info, Mint>,
     
     #[account(
         init,
         payer = authority,
         associated_token::mint = base_mint,
-        associated_token::authority = fee_vault
+        associated_token::authority = fee_vault,
+        associated_token::token_program = base_token_program
     )]
-    pub base_token_account: Account<'info, TokenAccount>,
+    pub base_token_account: InterfaceAccount<'info, TokenAccount>,
     
     #[account(
         init,
         payer = authority,
         associated_token::mint = quote_mint,
-        associated_token::authority = fee_vault
+        associated_token::authority = fee_vault,
+        associated_token::token_program = quote_token_program
+    )]
+    pub quote_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"program_config"],
+        bump = program_config.bump
     )]
-    pub quote_token_account: Account<'info, TokenAccount>,
+    pub program_config: Account<'info, ProgramConfig>,
+
+    /// Registry page the new vault is listed on, created when the previous page is full
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = VaultRegistry::LEN,
+        seeds = [b"vault_registry".as_ref(), &program_config.current_registry_page().to_le_bytes()],
+        bump
+    )]
+    pub vault_registry: Account<'info, VaultRegistry>,
     
     #[account(mut)]
     pub authority: Signer<'info>,
     
     pub system_program: Program<'info, System>,
-    pub token_program: Program<'info, Token>,
+    /// SPL Token or Token-2022, whichever owns `base_mint`
+    pub base_token_program: Interface<'info, TokenInterface>,
+    /// SPL Token or Token-2022, whichever owns `quote_mint`
+    pub quote_token_program: Interface<'info, TokenInterface>,
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub rent: Sysvar<'info, Rent>,
 }
 
 #[derive(Accounts)]
-pub struct CollectFees<'info> {
+pub struct DistributeFees<'info> {
     #[account(
         mut,
-        seeds = [b"fee_vault", dlmm_pool.key().as_ref()],
+        seeds = [b"fee_vault", fee_vault.dlmm_pool.as_ref(), fee_vault.creator_seed()],
         bump = fee_vault.bump
     )]
     pub fee_vault: Account<'info, FeeVault>,
+
+    #[account(address = fee_vault.base_mint @ FeeRoutingError::InvalidDlmmMint)]
+    pub base_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(address = fee_vault.quote_mint @ FeeRoutingError::InvalidDlmmMint)]
+    pub quote_mint: InterfaceAccount<'info, Mint>,
     
-    /// CHECK: This is the DLMM pool account
-    #[account(mut)]
-    pub dlmm_pool: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        associated_token::mint = base_mint,
+        associated_token::authority = fee_vault,
+        associated_token::token_program = base_token_program
+    )]
+    pub base_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        associated_token::mint = quote_mint,
+        associated_token::authority = fee_vault,
+        associated_token::token_program = quote_token_program
+    )]
+    pub quote_token_account: InterfaceAccount<'info, TokenAccount>,
     
     #[account(
         mut,
-        associated_token::mint = fee_vault.base_mint,
-        associated_token::authority = fee_vault
+        constraint = recipient_base_account.mint == fee_vault.base_mint @ FeeRoutingError::InvalidRecipientMint,
+        constraint = recipient_base_account.owner == recipient.key() @ FeeRoutingError::InvalidRecipientOwner
     )]
-    pub base_token_account: Account<'info, TokenAccount>,
+    pub recipient_base_account: InterfaceAccount<'info, TokenAccount>,
     
     #[account(
         mut,
-        associated_token::mint = fee_vault.quote_mint,
-        associated_token::authority = fee_vault
+        constraint = recipient_quote_account.mint == fee_vault.quote_mint @ FeeRoutingError::InvalidRecipientMint,
+        constraint = recipient_quote_account.owner == recipient.key() @ FeeRoutingError::InvalidRecipientOwner
     )]
-    pub quote_token_account: Account<'info, TokenAccount>,
+    pub recipient_quote_account: InterfaceAccount<'info, TokenAccount>,
     
-    pub collector: Signer<'info>,
-    pub token_program: Program<'info, Token>,
+    /// CHECK: Fee recipient
+    pub recipient: UncheckedAccount<'info>,
     
-    /// CHECK: This is the DLMM program
-    pub dlmm_program: UncheckedAccount<'info>,
+    /// CHECK: Vault authority or the configured distributor, approved through `MultisigApproval::authorize`
+    #[account(
+        constraint = fee_vault.has_role(Role::Distributor, &authority.key()) @ FeeRoutingError::UnauthorizedDistributor
+    )]
+    pub authority: UncheckedAccount<'info>,
+
+    #[account(address = fee_vault.base_token_program @ FeeRoutingError::InvalidTokenProgram)]
+    pub base_token_program: Interface<'info, TokenInterface>,
+
+    #[account(address = fee_vault.quote_token_program @ FeeRoutingError::InvalidTokenProgram)]
+    pub quote_token_program: Interface<'info, TokenInterface>,
 }
 
 #[derive(Accounts)]
-pub struct DistributeFees<'info> {
+pub struct UpdateFeeVault<'info> {
     #[account(
         mut,
-        seeds = [b"fee_vault", fee_vault.dlmm_pool.as_ref()],
-        bump = fee_vault.bump
+        seeds = [b"fee_vault", fee_vault.dlmm_pool.as_ref(), fee_vault.creator_seed()],
+        bump = fee_vault.bump,
+        has_one = authority
     )]
     pub fee_vault: Account<'info, FeeVault>,
     
+    /// CHECK: Vault authority, approved through `MultisigApproval::authorize`
+    pub authority: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetPauseFlags<'info> {
     #[account(
         mut,
-        associated_token::mint = fee_vault.base_mint,
-        associated_token::authority = fee_vault
+        seeds = [b"fee_vault", fee_vault.dlmm_pool.as_ref(), fee_vault.creator_seed()],
+        bump = fee_vault.bump,
+        constraint = fee_vault.has_role(Role::Guardian, &authority.key()) @ FeeRoutingError::MissingRole
     )]
-    pub base_token_account: Account<'info, TokenAccount>,
-    
+    pub fee_vault: Account<'info, FeeVault>,
+
+    /// CHECK: Vault authority or guardian, approved through `MultisigApproval::authorize`
+    pub authority: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptAuthority<'info> {
     #[account(
         mut,
-        associated_token::mint = fee_vault.quote_mint,
-        associated_token::authority = fee_vault
+        seeds = [b"fee_vault", fee_vault.dlmm_pool.as_ref(), fee_vault.creator_seed()],
+        bump = fee_vault.bump,
+        constraint = fee_vault.pending_authority == Some(pending_authority.key()) @ FeeRoutingError::NotPendingAuthority
     )]
-    pub quote_token_account: Account<'info, TokenAccount>,
-    
-    #[account(mut)]
-    pub recipient_base_account: Account<'info, TokenAccount>,
-    
+    pub fee_vault: Account<'info, FeeVault>,
+
+    /// CHECK: Pending authority, approved through `MultisigApproval::authorize`
+    pub pending_authority: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeDistributionConfig<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = DistributionConfig::LEN,
+        seeds = [b"distribution_config", fee_vault.key().as_ref()],
+        bump
+    )]
+    pub distribution_config: Account<'info, DistributionConfig>,
+
+    #[account(
+        seeds = [b"fee_vault", fee_vault.dlmm_pool.as_ref(), fee_vault.creator_seed()],
+        bump = fee_vault.bump,
+        constraint = fee_vault.has_role(Role::ConfigManager, &authority.key()) @ FeeRoutingError::MissingRole
+    )]
+    pub fee_vault: Account<'info, FeeVault>,
+
+    /// CHECK: Vault authority or config manager, approved through `MultisigApproval::authorize`
+    pub authority: UncheckedAccount<'info>,
+
     #[account(mut)]
-    pub recipient_quote_account: Account<'info, TokenAccount>,
-    
-    /// CHECK: Fee recipient
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateDistributionConfig<'info> {
+    #[account(
+        mut,
+        seeds = [b"distribution_config", fee_vault.key().as_ref()],
+        bump = distribution_config.bump,
+        has_one = fee_vault
+    )]
+    pub distribution_config: Account<'info, DistributionConfig>,
+
+    #[account(
+        seeds = [b"fee_vault", fee_vault.dlmm_pool.as_ref(), fee_vault.creator_seed()],
+        bump = fee_vault.bump,
+        constraint = fee_vault.has_role(Role::ConfigManager, &authority.key()) @ FeeRoutingError::MissingRole
+    )]
+    pub fee_vault: Account<'info, FeeVault>,
+
+    /// CHECK: Vault authority or config manager, approved through `MultisigApproval::authorize`
+    pub authority: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct OpenRecipientPosition<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = RecipientPosition::LEN,
+        seeds = [b"recipient_position", fee_vault.key().as_ref(), recipient.key().as_ref()],
+        bump
+    )]
+    pub recipient_position: Account<'info, RecipientPosition>,
+
+    #[account(
+        mut,
+        seeds = [b"fee_vault", fee_vault.dlmm_pool.as_ref(), fee_vault.creator_seed()],
+        bump = fee_vault.bump,
+        constraint = fee_vault.has_role(Role::ConfigManager, &authority.key()) @ FeeRoutingError::MissingRole
+    )]
+    pub fee_vault: Account<'info, FeeVault>,
+
+    /// CHECK: Recipient the position pays out to
     pub recipient: UncheckedAccount<'info>,
-    
-    pub authority: Signer<'info>,
-    pub token_program: Program<'info, Token>,
+
+    /// CHECK: Vault authority or config manager, approved through `MultisigApproval::authorize`
+    pub authority: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct UpdateFeeVault<'info> {
+pub struct UpdateRecipientShares<'info> {
+    #[account(
+        mut,
+        seeds = [b"recipient_position", fee_vault.key().as_ref(), recipient_position.recipient.as_ref()],
+        bump = recipient_position.bump,
+        has_one = fee_vault
+    )]
+    pub recipient_position: Account<'info, RecipientPosition>,
+
     #[account(
         mut,
-        seeds = [b"fee_vault", fee_vault.dlmm_pool.as_ref()],
+        seeds = [b"fee_vault", fee_vault.dlmm_pool.as_ref(), fee_vault.creator_seed()],
         bump = fee_vault.bump,
-        has_one = authority
+        constraint = fee_vault.has_role(Role::ConfigManager, &authority.key()) @ FeeRoutingError::MissingRole
     )]
     pub fee_vault: Account<'info, FeeVault>,
-    
-    pub authority: Signer<'info>,
+
+    /// CHECK: Vault authority or config manager, approved through `MultisigApproval::authorize`
+    pub authority: UncheckedAccount<'info>,
 }
 
 #[account]
@@ -282,11 +1222,72 @@ pub struct FeeVault {
     pub base_mint: Pubkey,
     pub quote_mint: Pubkey,
     pub bump: u8,
+    /// LP fees that landed in the vault, net of Token-2022 transfer fees
     pub total_collected_base: u64,
     pub total_collected_quote: u64,
     pub creation_time: i64,
+    pub total_shares: u64,
+    pub fee_per_share_base: u128,
+    pub fee_per_share_quote: u128,
+    pub reserved_base: u64,
+    pub reserved_quote: u64,
+    pub distributor: Option<Pubkey>,
+    pub total_protocol_fees_base: u64,
+    pub total_protocol_fees_quote: u64,
+    pub reward_mints: [Pubkey; 2],
+    pub total_collected_rewards: [u64; 2],
+    pub base_token_program: Pubkey,
+    pub quote_token_program: Pubkey,
+    /// LP fees the pool paid out, before Token-2022 transfer fees were withheld
+    pub total_collected_gross_base: u64,
+    pub total_collected_gross_quote: u64,
+    /// Proposed authority, which takes over once it calls `accept_authority`
+    pub pending_authority: Option<Pubkey>,
+    /// Seconds a queued configuration change waits before it can be executed.
+    /// While non-zero, configuration can only change through the queue.
+    pub config_delay: i64,
+    /// Id the next queued configuration change is stored under
+    pub next_change_id: u64,
+    /// `PAUSE_*` bitflags of the operations currently halted
+    pub paused: u8,
+    /// Key allowed to set the pause flags alongside the authority
+    pub guardian: Option<Pubkey>,
+    /// Key allowed to manage the split table and recipient positions alongside the authority
+    pub config_manager: Option<Pubkey>,
+    /// Protocol fee sent to the treasury out of collected LP and protocol fees
+    pub total_skimmed_base: u64,
+    pub total_skimmed_quote: u64,
+    /// Key that created the vault, part of its PDA seeds so several vaults can share a pool.
+    /// Left at `Pubkey::default()` for first-release vaults, which keep their `[fee_vault, pool]` address
+    pub creator: Pubkey,
+    /// Layout version, `FEE_VAULT_VERSION` once created or migrated. Vaults
+    /// from the first release are v0 (`FeeVaultV0`) and need `migrate_fee_vault`.
+    pub version: u8,
+    /// DLMM positions registered with `register_vault_position`
+    pub position_count: u32,
+    /// Open `RecipientPosition`s
+    pub recipient_count: u32,
+    /// Queued configuration changes not yet executed or cancelled
+    pub queued_change_count: u32,
+    /// Zeroed space that later versions can take new fields from without a realloc
+    pub reserved: [u8; FEE_VAULT_RESERVED],
 }
 
+/// Halts `collect_position_fees`, `collect_many_position_fees`, `collect_protocol_fees`
+/// and `collect_position_rewards`
+pub const PAUSE_COLLECT: u8 = 1 << 0;
+/// Halts `distribute_fees`, `distribute_to_recipients` and `distribute_rewards_to_recipients`
+pub const PAUSE_DISTRIBUTE: u8 = 1 << 1;
+/// Halts `claim`
+pub const PAUSE_CLAIM: u8 = 1 << 2;
+pub const PAUSE_ALL: u8 = PAUSE_COLLECT | PAUSE_DISTRIBUTE | PAUSE_CLAIM;
+
+/// Layout version written by `initialize_fee_vault` and `migrate_fee_vault`
+pub const FEE_VAULT_VERSION: u8 = 1;
+
+/// Bytes kept free at the end of `FeeVault` for fields added by later versions
+pub const FEE_VAULT_RESERVED: usize = 116;
+
 impl FeeVault {
     pub const LEN: usize = 8 + // discriminator
         32 + // authority
@@ -296,7 +1297,265 @@ impl FeeVault {
         1 +  // bump
         8 +  // total_collected_base
         8 +  // total_collected_quote
-        8;   // creation_time
+        8 +  // creation_time
+        8 +  // total_shares
+        16 + // fee_per_share_base
+        16 + // fee_per_share_quote
+        8 +  // reserved_base
+        8 +  // reserved_quote
+        1 + 32 + // distributor
+        8 +  // total_protocol_fees_base
+        8 +  // total_protocol_fees_quote
+        32 * NUM_REWARDS + // reward_mints
+        8 * NUM_REWARDS + // total_collected_rewards
+        32 + // base_token_program
+        32 + // quote_token_program
+        8 +  // total_collected_gross_base
+        8 +  // total_collected_gross_quote
+        1 + 32 + // pending_authority
+        8 +  // config_delay
+        8 +  // next_change_id
+        1 +  // paused
+        1 + 32 + // guardian
+        1 + 32 + // config_manager
+        8 +  // total_skimmed_base
+        8 +  // total_skimmed_quote
+        32 + // creator
+        1 +  // version
+        4 +  // position_count
+        4 +  // recipient_count
+        4 +  // queued_change_count
+        FEE_VAULT_RESERVED; // reserved
+
+    /// Byte offset of `dlmm_pool`, for `getProgramAccounts` memcmp filters that list a pool's vaults
+    pub const DLMM_POOL_OFFSET: usize = 8 + 32;
+
+    /// PDA of the vault `creator` opened for `dlmm_pool`, at `["fee_vault", dlmm_pool, creator]`
+    pub fn find_address(dlmm_pool: &Pubkey, creator: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[b"fee_vault", dlmm_pool.as_ref(), creator.as_ref()], &crate::ID)
+    }
+
+    /// Creator seed of the vault's PDA, empty for first-release vaults so they derive `[fee_vault, pool]`
+    pub fn creator_seed(&self) -> &[u8] {
+        if self.creator == Pubkey::default() {
+            &[]
+        } else {
+            self.creator.as_ref()
+        }
+    }
+
+    /// Seeds the vault PDA signs its CPIs with
+    pub fn signer_seeds(&self) -> [&[u8]; 4] {
+        [
+            b"fee_vault",
+            self.dlmm_pool.as_ref(),
+            self.creator_seed(),
+            std::slice::from_ref(&self.bump),
+        ]
+    }
+
+    /// Direct configuration changes are only allowed while no timelock is set
+    pub fn require_untimelocked(&self) -> Result<()> {
+        require!(self.config_delay == 0, FeeRoutingError::ConfigTimelocked);
+        Ok(())
+    }
+
+    /// Fail with `Paused` if any of `flags` is set
+    pub fn require_not_paused(&self, flags: u8) -> Result<()> {
+        require!(self.paused & flags == 0, FeeRoutingError::Paused);
+        Ok(())
+    }
+
+    /// Whether `key` holds `role`, either directly or as the admin
+    pub fn has_role(&self, role: Role, key: &Pubkey) -> bool {
+        *key == self.authority || VaultRoles::holder(self, role).as_ref() == Some(key)
+    }
+
+    /// Add collected LP fees to the net and gross running totals
+    pub fn record_fees(&mut self, collected: &FeeAmounts) -> Result<()> {
+        self.total_collected_base = self.total_collected_base
+            .checked_add(collected.base)
+            .ok_or(FeeRoutingError::MathOverflow)?;
+        self.total_collected_quote = self.total_collected_quote
+            .checked_add(collected.quote)
+            .ok_or(FeeRoutingError::MathOverflow)?;
+        self.total_collected_gross_base = self.total_collected_gross_base
+            .checked_add(collected.base_gross()?)
+            .ok_or(FeeRoutingError::MathOverflow)?;
+        self.total_collected_gross_quote = self.total_collected_gross_quote
+            .checked_add(collected.quote_gross()?)
+            .ok_or(FeeRoutingError::MathOverflow)?;
+        Ok(())
+    }
+
+    /// Add a farming reward claim to the per-slot totals, pinning the slot's mint on first use
+    pub fn record_reward(&mut self, index: usize, mint: Pubkey, amount: u64) -> Result<()> {
+        require!(index < NUM_REWARDS, FeeRoutingError::InvalidRewardIndex);
+        if self.reward_mints[index] == Pubkey::default() {
+            self.reward_mints[index] = mint;
+        }
+        require_keys_eq!(self.reward_mints[index], mint, FeeRoutingError::InvalidRewardAccount);
+
+        self.total_collected_rewards[index] = self.total_collected_rewards[index]
+            .checked_add(amount)
+            .ok_or(FeeRoutingError::MathOverflow)?;
+        Ok(())
+    }
+}
+
+#[account]
+pub struct DistributionConfig {
+    pub fee_vault: Pubkey,
+    pub bump: u8,
+    pub recipients: Vec<RecipientWeight>,
+}
+
+impl DistributionConfig {
+    pub const LEN: usize = 8 + // discriminator
+        32 + // fee_vault
+        1 +  // bump
+        4 + RecipientWeight::LEN * MAX_RECIPIENTS; // recipients
+}
+
+/// A configuration change waiting for its ETA, at `["config_change", fee_vault, id]`
+#[account]
+pub struct QueuedConfigChange {
+    pub fee_vault: Pubkey,
+    pub id: u64,
+    /// Paid the account's rent and gets it back when the change is executed or cancelled
+    pub payer: Pubkey,
+    pub bump: u8,
+    /// Unix timestamp from which the change can be executed
+    pub eta: i64,
+    pub change: ConfigChange,
+}
+
+impl QueuedConfigChange {
+    pub const LEN: usize = 8 + // discriminator
+        32 + // fee_vault
+        8 +  // id
+        32 + // payer
+        1 +  // bump
+        8 +  // eta
+        ConfigChange::MAX_LEN; // change
+}
+
+/// Program-wide policy singleton at `["program_config"]`
+#[account]
+pub struct ProgramConfig {
+    pub admin: Pubkey,
+    pub bump: u8,
+    pub protocol_fee_bps: u16,
+    /// DLMM programs collection instructions may CPI into
+    pub allowed_dlmm_programs: Vec<Pubkey>,
+    /// `config_delay` new fee vaults start with
+    pub default_config_delay: i64,
+    /// Fee vaults registered so far, across all registry pages
+    pub vault_count: u64,
+    /// Owner of the ATAs the protocol fee is sent to
+    pub treasury: Pubkey,
+}
+
+impl ProgramConfig {
+    pub const LEN: usize = 8 + // discriminator
+        32 + // admin
+        1 +  // bump
+        2 +  // protocol_fee_bps
+        4 + 32 * MAX_ALLOWED_DLMM_PROGRAMS + // allowed_dlmm_programs
+        8 +  // default_config_delay
+        8 +  // vault_count
+        32;  // treasury
+
+    pub fn allows_dlmm_program(&self, program_id: &Pubkey) -> bool {
+        self.allowed_dlmm_programs.contains(program_id)
+    }
+
+    /// Page the next registered vault goes on
+    pub fn current_registry_page(&self) -> u32 {
+        (self.vault_count / VAULTS_PER_PAGE) as u32
+    }
+}
+
+/// One page of the fee vault registry, at `["vault_registry", page]`
+#[account]
+pub struct VaultRegistry {
+    pub page: u32,
+    pub bump: u8,
+    pub vaults: Vec<Pubkey>,
+}
+
+impl VaultRegistry {
+    pub const LEN: usize = 8 + // discriminator
+        4 +  // page
+        1 +  // bump
+        4 + 32 * VAULTS_PER_PAGE as usize; // vaults
+}
+
+/// A DLMM position registered with its fee vault, at `["vault_position", fee_vault, position]`
+#[account]
+pub struct VaultPosition {
+    pub fee_vault: Pubkey,
+    pub position: Pubkey,
+    /// DLMM program that owned the position when it was registered
+    pub dlmm_program: Pubkey,
+    /// Paid the account's rent and gets it back when the position is deregistered
+    pub payer: Pubkey,
+    pub bump: u8,
+}
+
+impl VaultPosition {
+    pub const LEN: usize = 8 + // discriminator
+        32 + // fee_vault
+        32 + // position
+        32 + // dlmm_program
+        32 + // payer
+        1;   // bump
+}
+
+/// M-of-N signer set at `["vault_multisig", fee_vault]` that can hold vault roles
+#[account]
+pub struct VaultMultisig {
+    pub fee_vault: Pubkey,
+    pub bump: u8,
+    /// Signing members needed to approve an instruction
+    pub threshold: u8,
+    pub members: Vec<Pubkey>,
+}
+
+impl VaultMultisig {
+    pub const LEN: usize = 8 + // discriminator
+        32 + // fee_vault
+        1 +  // bump
+        1 +  // threshold
+        4 + 32 * MAX_MULTISIG_MEMBERS; // members
+}
+
+#[account]
+pub struct RecipientPosition {
+    pub fee_vault: Pubkey,
+    pub recipient: Pubkey,
+    pub bump: u8,
+    pub shares: u64,
+    pub fee_per_share_base_checkpoint: u128,
+    pub fee_per_share_quote_checkpoint: u128,
+    pub pending_base: u64,
+    pub pending_quote: u64,
+    pub total_claimed_base: u64,
+    pub total_claimed_quote: u64,
+}
+
+impl RecipientPosition {
+    pub const LEN: usize = 8 + // discriminator
+        32 + // fee_vault
+        32 + // recipient
+        1 +  // bump
+        8 +  // shares
+        16 + // fee_per_share_base_checkpoint
+        16 + // fee_per_share_quote_checkpoint
+        8 +  // pending_base
+        8 +  // pending_quote
+        8 +  // total_claimed_base
+        8;   // total_claimed_quote
 }
 
 #[error_code]
@@ -313,4 +1572,94 @@ pub enum FeeRoutingError {
     MathOverflow,
     #[msg("Unauthorized")]
     Unauthorized,
+    #[msg("Recipient count must be between 1 and the configured maximum")]
+    InvalidRecipientCount,
+    #[msg("Recipient weights must be non-zero and sum to 10000 bps")]
+    InvalidRecipientWeights,
+    #[msg("Recipient listed more than once")]
+    DuplicateRecipient,
+    #[msg("Recipient token account does not match the distribution config")]
+    InvalidRecipientAccount,
+    #[msg("Recipient position must hold at least one share")]
+    InvalidShares,
+    #[msg("Signer is neither the vault authority nor the configured distributor")]
+    UnauthorizedDistributor,
+    #[msg("Recipient token account mint does not match the vault mint")]
+    InvalidRecipientMint,
+    #[msg("Recipient token account is not owned by the recipient")]
+    InvalidRecipientOwner,
+    #[msg("Account is not owned by, or derived from, the DLMM program being invoked")]
+    InvalidDlmmProgram,
+    #[msg("DLMM position does not belong to the vault's pool")]
+    InvalidDlmmPosition,
+    #[msg("DLMM position is not owned by the fee vault")]
+    InvalidPositionOwner,
+    #[msg("DLMM reserve account does not match the pool")]
+    InvalidDlmmReserve,
+    #[msg("Token mint does not match the pool and vault mints")]
+    InvalidDlmmMint,
+    #[msg("Position batch is empty, over the batch limit, or missing bin arrays")]
+    InvalidPositionBatch,
+    #[msg("Fee vault is not a claim fee operator of the DLMM program")]
+    InvalidProtocolFeeOwner,
+    #[msg("Reward index is out of range or the reward slot has not been collected")]
+    InvalidRewardIndex,
+    #[msg("Reward account does not match the pool reward slot or the vault reward ATA")]
+    InvalidRewardAccount,
+    #[msg("Reward mint is a pool mint and must be routed with the base and quote fees")]
+    RewardMintIsPoolMint,
+    #[msg("Token program does not own the vault mint")]
+    InvalidTokenProgram,
+    #[msg("Memo program account does not match the SPL Memo program ID")]
+    InvalidMemoProgram,
+    #[msg("Transfer hook accounts are missing or malformed")]
+    InvalidTransferHookAccounts,
+    #[msg("Signer is not the pending vault authority")]
+    NotPendingAuthority,
+    #[msg("No authority transfer is pending")]
+    NoPendingAuthority,
+    #[msg("Vault has a config delay; queue the change with queue_config_change")]
+    ConfigTimelocked,
+    #[msg("Queued configuration change is not executable yet")]
+    TimelockNotElapsed,
+    #[msg("Config delay must be between zero and the maximum delay")]
+    InvalidConfigDelay,
+    #[msg("Account the queued configuration change applies to is missing or does not match")]
+    MissingConfigChangeAccount,
+    #[msg("Operation is paused on this vault")]
+    Paused,
+    #[msg("Pause flags contain unknown bits")]
+    InvalidPauseFlags,
+    #[msg("Signer does not hold the role this instruction requires")]
+    MissingRole,
+    #[msg("The admin role moves with propose_authority and accept_authority")]
+    InvalidRole,
+    #[msg("Multisig needs 1 to 10 distinct members and a threshold between 1 and the member count")]
+    InvalidMultisig,
+    #[msg("Not enough multisig members signed")]
+    MultisigThresholdNotMet,
+    #[msg("Protocol fee or DLMM program allowlist is out of range")]
+    InvalidProgramConfig,
+    #[msg("Vault registry page is not the current page, or does not list the vault")]
+    InvalidVaultRegistry,
+    #[msg("DLMM program is not on the program config allowlist")]
+    DlmmProgramNotAllowed,
+    #[msg("Treasury token accounts are required while the protocol fee is non-zero")]
+    MissingTreasuryAccount,
+    #[msg("Vault still holds or owes tokens")]
+    VaultNotEmpty,
+    #[msg("A position owned by the vault still holds liquidity")]
+    PositionHasLiquidity,
+    #[msg("Account is not a fee vault in a layout that can be migrated")]
+    InvalidFeeVaultVersion,
+    #[msg("Fee vault already has the current layout")]
+    FeeVaultAlreadyMigrated,
+    #[msg("Bin arrays must cover the position's whole bin range")]
+    InvalidBinArrays,
+    #[msg("DLMM position is not registered with the fee vault")]
+    PositionNotRegistered,
+    #[msg("Vault still has registered positions, recipient positions or queued changes")]
+    VaultHasOpenAccounts,
+    #[msg("Recipient position still has fees to claim")]
+    RecipientPositionNotEmpty,
 }
\ No newline at end of file
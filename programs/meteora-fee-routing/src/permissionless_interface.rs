@@ -1,54 +1,331 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{Token, TokenAccount, Mint};
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
 use crate::dlmm_integration::meteora_dlmm_types::*;
-use crate::dlmm_integration::cpi_instructions;
+use anchor_lang::solana_program::compute_units::sol_remaining_compute_units;
+use crate::dlmm_integration::cpi_instructions::{self, AccountsType};
+
+/// Upper bound on positions per batch, set by the transaction account limit
+pub const MAX_POSITIONS_PER_BATCH: usize = 16;
+
+/// Compute units reserved for each `claim_fee2` CPI. The batch stops early
+/// rather than running out of budget halfway through a claim.
+pub const COMPUTE_UNITS_PER_CLAIM: u64 = 60_000;
 
 /// Permissionless interface for fee routing operations
 pub struct PermissionlessInterface;
 
 impl PermissionlessInterface {
-    /// Anyone can call this to collect fees from a DLMM position
-    pub fn collect_position_fees(
-        ctx: &Context<CollectPositionFees>,
-    ) -> Result<(u64, u64)> {
-        // Get balances before fee collection
-        let base_balance_before = ctx.accounts.base_token_account.amount;
-        let quote_balance_before = ctx.accounts.quote_token_account.amount;
+    /// Anyone can call this to collect fees from a DLMM position.
+    ///
+    /// `remaining_accounts` starts with the `bin_array_count` bin arrays the
+    /// position spans, followed by the transfer hook accounts for hooked
+    /// Token-2022 mints.
+    pub fn collect_position_fees<'info>(
+        ctx: &mut Context<'_, '_, 'info, 'info, CollectPositionFees<'info>>,
+        bin_array_count: u8,
+    ) -> Result<FeeAmounts> {
+        VaultPositions::require_registered(
+            &ctx.accounts.vault_position,
+            &ctx.accounts.fee_vault.key(),
+            ctx.accounts.position.key,
+        )?;
+        let bin_range = Self::bin_range(&ctx.accounts.position)?;
+        let (bin_arrays, hook_accounts) = Self::split_bin_arrays(
+            ctx.remaining_accounts,
+            bin_array_count,
+            bin_range,
+            &ctx.accounts.dlmm_program.key(),
+        )?;
 
         // Call DLMM program to claim position fees
-        let cpi_accounts = cpi_instructions::ClaimFee {
-            position: ctx.accounts.position.clone(),
-            lb_pair: ctx.accounts.lb_pair.clone(),
-            user_token_x: ctx.accounts.base_token_account.clone(),
-            user_token_y: ctx.accounts.quote_token_account.clone(),
-            reserve_x: ctx.accounts.reserve_x.clone(),
-            reserve_y: ctx.accounts.reserve_y.clone(),
-            token_x_mint: ctx.accounts.base_mint.clone(),
-            token_y_mint: ctx.accounts.quote_mint.clone(),
-            token_program: ctx.accounts.token_program.clone(),
-            owner: ctx.accounts.fee_vault.clone(),
+        let cpi_accounts = cpi_instructions::ClaimFee2 {
+            lb_pair: ctx.accounts.lb_pair.to_account_info(),
+            position: ctx.accounts.position.to_account_info(),
+            owner: ctx.accounts.fee_vault.to_account_info(),
+            reserve_x: Box::new(ctx.accounts.reserve_x.clone()),
+            reserve_y: Box::new(ctx.accounts.reserve_y.clone()),
+            user_token_x: Box::new(ctx.accounts.base_token_account.clone()),
+            user_token_y: Box::new(ctx.accounts.quote_token_account.clone()),
+            token_x_mint: Box::new(ctx.accounts.base_mint.clone()),
+            token_y_mint: Box::new(ctx.accounts.quote_mint.clone()),
+            token_program_x: ctx.accounts.base_token_program.clone(),
+            token_program_y: ctx.accounts.quote_token_program.clone(),
+            memo_program: ctx.accounts.memo_program.to_account_info(),
+            event_authority: ctx.accounts.event_authority.to_account_info(),
         };
+        let accounts = &mut ctx.accounts;
+        Self::claim_into_vault(
+            &accounts.fee_vault,
+            &accounts.dlmm_program,
+            cpi_accounts,
+            bin_arrays,
+            bin_range,
+            &mut accounts.base_token_account,
+            &mut accounts.quote_token_account,
+            (&accounts.base_mint, &accounts.quote_mint),
+            hook_accounts,
+        )
+    }
+
+    /// Collect fees from every position passed in `remaining_accounts`, each
+    /// followed by its `VaultPosition` and its bin arrays, `bin_array_counts[i]`
+    /// of them for position `i`. Any accounts after the batch are transfer hook
+    /// accounts shared by every claim.
+    ///
+    /// Returns the summed amounts and the number of positions claimed, which is
+    /// lower than the number passed when the compute budget runs out first.
+    pub fn collect_many_position_fees<'info>(
+        ctx: &mut Context<'_, '_, 'info, 'info, CollectManyPositionFees<'info>>,
+        bin_array_counts: &[u8],
+    ) -> Result<(FeeAmounts, usize)> {
+        let position_count = bin_array_counts.len();
+        let batch_len = Self::batch_len(bin_array_counts);
+        require!(
+            position_count > 0
+                && position_count <= MAX_POSITIONS_PER_BATCH
+                && batch_len <= ctx.remaining_accounts.len(),
+            FeeRoutingError::InvalidPositionBatch
+        );
+        let (mut positions, hook_accounts) = ctx.remaining_accounts.split_at(batch_len);
+
+        let accounts = &mut ctx.accounts;
+        let lb_pair = accounts.lb_pair.key();
+        let fee_vault = accounts.fee_vault.key();
+        let dlmm_program = accounts.dlmm_program.key();
+
+        let mut total = FeeAmounts::default();
+        let mut claimed = 0;
+
+        for &bin_array_count in bin_array_counts {
+            if sol_remaining_compute_units() < COMPUTE_UNITS_PER_CLAIM {
+                msg!(
+                    "Compute budget exhausted after {} of {} positions",
+                    claimed,
+                    position_count
+                );
+                break;
+            }
+
+            let [position, vault_position, rest @ ..] = positions else {
+                return err!(FeeRoutingError::InvalidPositionBatch);
+            };
+            let bin_range = Self::validate_position(position, &dlmm_program, &lb_pair, &fee_vault)?;
+            VaultPositions::require_registered(vault_position, &fee_vault, position.key)?;
+            let (bin_arrays, rest) = Self::split_bin_arrays(rest, bin_array_count, bin_range, &dlmm_program)?;
+            positions = rest;
+
+            let cpi_accounts = cpi_instructions::ClaimFee2 {
+                lb_pair: accounts.lb_pair.to_account_info(),
+                position: position.clone(),
+                owner: accounts.fee_vault.to_account_info(),
+                reserve_x: Box::new(accounts.reserve_x.clone()),
+                reserve_y: Box::new(accounts.reserve_y.clone()),
+                user_token_x: Box::new(accounts.base_token_account.clone()),
+                user_token_y: Box::new(accounts.quote_token_account.clone()),
+                token_x_mint: Box::new(accounts.base_mint.clone()),
+                token_y_mint: Box::new(accounts.quote_mint.clone()),
+                token_program_x: accounts.base_token_program.clone(),
+                token_program_y: accounts.quote_token_program.clone(),
+                memo_program: accounts.memo_program.to_account_info(),
+                event_authority: accounts.event_authority.to_account_info(),
+            };
+
+            let collected = Self::claim_into_vault(
+                &accounts.fee_vault,
+                &accounts.dlmm_program,
+                cpi_accounts,
+                bin_arrays,
+                bin_range,
+                &mut accounts.base_token_account,
+                &mut accounts.quote_token_account,
+                (&accounts.base_mint, &accounts.quote_mint),
+                hook_accounts,
+            )?;
+
+            msg!(
+                "Position {} fees collected - Base: {}, Quote: {}",
+                position.key(),
+                collected.base,
+                collected.quote
+            );
+
+            total = total.checked_add(collected)?;
+            claimed += 1;
+        }
 
-        let fee_vault = &ctx.accounts.fee_vault;
-        let seeds = &[
-            b"fee_vault",
-            fee_vault.dlmm_pool.as_ref(),
-            &[fee_vault.bump],
-        ];
+        Ok((total, claimed))
+    }
+
+    /// Check a remaining-accounts position the way `CollectPositionFees` does
+    /// and return its `(lower_bin_id, upper_bin_id)`
+    fn validate_position(
+        position: &AccountInfo,
+        dlmm_program: &Pubkey,
+        lb_pair: &Pubkey,
+        fee_vault: &Pubkey,
+    ) -> Result<(i32, i32)> {
+        require_keys_eq!(*position.owner, *dlmm_program, FeeRoutingError::InvalidDlmmPosition);
+
+        let data = position.try_borrow_data()?;
+        let position = DlmmPosition::from_bytes(&data)
+            .map_err(|_| error!(FeeRoutingError::InvalidDlmmPosition))?;
+        require_keys_eq!(position.lb_pair(), *lb_pair, FeeRoutingError::InvalidDlmmPosition);
+        require_keys_eq!(position.owner(), *fee_vault, FeeRoutingError::InvalidPositionOwner);
+
+        Ok((position.lower_bin_id(), position.upper_bin_id()))
+    }
+
+    /// Number of remaining accounts a `collect_many_position_fees` batch takes
+    /// up before its transfer hook accounts
+    pub(crate) fn batch_len(bin_array_counts: &[u8]) -> usize {
+        bin_array_counts.iter().map(|count| 2 + *count as usize).sum()
+    }
+
+    /// Split a position's bin arrays off the front of `accounts`. There must be
+    /// one for every bin array the position's `(lower_bin_id, upper_bin_id)`
+    /// range touches, each owned by the DLMM program. DLMM checks which arrays
+    /// they are.
+    pub(crate) fn split_bin_arrays<'a, 'info>(
+        accounts: &'a [AccountInfo<'info>],
+        bin_array_count: u8,
+        (lower_bin_id, upper_bin_id): (i32, i32),
+        dlmm_program: &Pubkey,
+    ) -> Result<(&'a [AccountInfo<'info>], &'a [AccountInfo<'info>])> {
+        let spanned = bin_array_index(upper_bin_id) - bin_array_index(lower_bin_id) + 1;
+        require!(
+            bin_array_count as i64 == spanned && bin_array_count as usize <= accounts.len(),
+            FeeRoutingError::InvalidBinArrays
+        );
+
+        let (bin_arrays, rest) = accounts.split_at(bin_array_count as usize);
+        for bin_array in bin_arrays {
+            require_keys_eq!(*bin_array.owner, *dlmm_program, FeeRoutingError::InvalidDlmmProgram);
+        }
+        Ok((bin_arrays, rest))
+    }
+
+    /// `(lower_bin_id, upper_bin_id)` of an already validated position
+    pub(crate) fn bin_range(position: &AccountInfo) -> Result<(i32, i32)> {
+        let data = position.try_borrow_data()?;
+        let position = DlmmPosition::from_bytes(&data)?;
+        Ok((position.lower_bin_id(), position.upper_bin_id()))
+    }
+
+    /// Run `claim_fee2` over the position's whole bin range, signed by the vault
+    /// PDA, and return what landed in the vault. Hook accounts go ahead of the
+    /// bin arrays, described by the `RemainingAccountsInfo` slices.
+    #[allow(clippy::too_many_arguments)]
+    fn claim_into_vault<'info>(
+        fee_vault: &Account<'info, FeeVault>,
+        dlmm_program: &AccountInfo<'info>,
+        cpi_accounts: cpi_instructions::ClaimFee2<'info>,
+        bin_arrays: &[AccountInfo<'info>],
+        (min_bin_id, max_bin_id): (i32, i32),
+        base_token_account: &mut InterfaceAccount<'info, TokenAccount>,
+        quote_token_account: &mut InterfaceAccount<'info, TokenAccount>,
+        mints: (&InterfaceAccount<'info, Mint>, &InterfaceAccount<'info, Mint>),
+        hook_accounts: &[AccountInfo<'info>],
+    ) -> Result<FeeAmounts> {
+        let (remaining_accounts_info, mut remaining_accounts) = TransferHooks::dlmm_remaining_accounts(
+            &[
+                (AccountsType::TransferHookX, mints.0.to_account_info()),
+                (AccountsType::TransferHookY, mints.1.to_account_info()),
+            ],
+            hook_accounts,
+        )?;
+        remaining_accounts.extend_from_slice(bin_arrays);
+
+        // Get balances before fee collection
+        let base_balance_before = base_token_account.amount;
+        let quote_balance_before = quote_token_account.amount;
+
+        let seeds = fee_vault.signer_seeds();
         let signer = &[&seeds[..]];
 
-        let cpi_program = ctx.accounts.dlmm_program.to_account_info();
-        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+        let cpi_ctx = CpiContext::new_with_signer(dlmm_program.clone(), cpi_accounts, signer)
+            .with_remaining_accounts(remaining_accounts);
+        cpi_instructions::claim_fee2(cpi_ctx, min_bin_id, max_bin_id, remaining_accounts_info)?;
 
-        cpi_instructions::claim_fee(cpi_ctx)?;
+        Self::collected_since(
+            base_token_account,
+            quote_token_account,
+            (base_balance_before, quote_balance_before),
+            mints,
+        )
+    }
 
-        // Calculate collected amounts
-        let base_collected = ctx.accounts.base_token_account.amount
+    /// Reload the vault token accounts after a CPI and return how much each
+    /// gained, along with the transfer fees the mints withheld on the way in
+    fn collected_since<'info>(
+        base_token_account: &mut InterfaceAccount<'info, TokenAccount>,
+        quote_token_account: &mut InterfaceAccount<'info, TokenAccount>,
+        (base_balance_before, quote_balance_before): (u64, u64),
+        (base_mint, quote_mint): (&InterfaceAccount<'info, Mint>, &InterfaceAccount<'info, Mint>),
+    ) -> Result<FeeAmounts> {
+        base_token_account.reload()?;
+        quote_token_account.reload()?;
+        let base = base_token_account.amount
             .saturating_sub(base_balance_before);
-        let quote_collected = ctx.accounts.quote_token_account.amount
+        let quote = quote_token_account.amount
             .saturating_sub(quote_balance_before);
 
-        Ok((base_collected, quote_collected))
+        Ok(FeeAmounts {
+            base,
+            quote,
+            base_transfer_fee: TransferFees::on_receive(&base_mint.to_account_info(), base)?,
+            quote_transfer_fee: TransferFees::on_receive(&quote_mint.to_account_info(), quote)?,
+        })
+    }
+
+    /// Claim all of the pool's protocol fees into the vault. Only works while
+    /// the vault PDA is a DLMM claim fee operator, which the accounts struct
+    /// enforces. `remaining_accounts` are the transfer hook accounts, forwarded
+    /// to DLMM as `RemainingAccountsInfo` slices.
+    pub fn collect_protocol_fees<'info>(
+        ctx: &mut Context<'_, '_, 'info, 'info, CollectProtocolFees<'info>>,
+    ) -> Result<FeeAmounts> {
+        let accounts = &mut ctx.accounts;
+        let base_balance_before = accounts.base_token_account.amount;
+        let quote_balance_before = accounts.quote_token_account.amount;
+
+        let (remaining_accounts_info, remaining_accounts) = TransferHooks::dlmm_remaining_accounts(
+            &[
+                (AccountsType::TransferHookX, accounts.base_mint.to_account_info()),
+                (AccountsType::TransferHookY, accounts.quote_mint.to_account_info()),
+            ],
+            ctx.remaining_accounts,
+        )?;
+
+        let cpi_accounts = cpi_instructions::ClaimProtocolFee {
+            lb_pair: accounts.lb_pair.to_account_info(),
+            reserve_x: Box::new(accounts.reserve_x.clone()),
+            reserve_y: Box::new(accounts.reserve_y.clone()),
+            token_x_mint: Box::new(accounts.base_mint.clone()),
+            token_y_mint: Box::new(accounts.quote_mint.clone()),
+            receiver_token_x: Box::new(accounts.base_token_account.clone()),
+            receiver_token_y: Box::new(accounts.quote_token_account.clone()),
+            claim_fee_operator: accounts.claim_fee_operator.to_account_info(),
+            operator: accounts.fee_vault.to_account_info(),
+            token_x_program: accounts.base_token_program.clone(),
+            token_y_program: accounts.quote_token_program.clone(),
+            memo_program: accounts.memo_program.to_account_info(),
+        };
+
+        let fee_vault = &accounts.fee_vault;
+        let seeds = fee_vault.signer_seeds();
+        let signer = &[&seeds[..]];
+
+        let cpi_program = accounts.dlmm_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer)
+            .with_remaining_accounts(remaining_accounts);
+        cpi_instructions::claim_protocol_fee(cpi_ctx, u64::MAX, u64::MAX, remaining_accounts_info)?;
+
+        Self::collected_since(
+            &mut accounts.base_token_account,
+            &mut accounts.quote_token_account,
+            (base_balance_before, quote_balance_before),
+            (&accounts.base_mint, &accounts.quote_mint),
+        )
     }
 }
 
@@ -56,49 +333,341 @@ impl PermissionlessInterface {
 pub struct CollectPositionFees<'info> {
     #[account(
         mut,
-        seeds = [b"fee_vault", dlmm_pool.key().as_ref()],
+        seeds = [b"fee_vault", lb_pair.key().as_ref(), fee_vault.creator_seed()],
         bump = fee_vault.bump
     )]
     pub fee_vault: Account<'info, FeeVault>,
 
-    /// CHECK: DLMM pool account
-    #[account(mut)]
-    pub dlmm_pool: UncheckedAccount<'info>,
+    /// CHECK: DLMM `Position` or `PositionV2`, decoded by discriminator
+    #[account(
+        mut,
+        owner = dlmm_program.key() @ FeeRoutingError::InvalidDlmmPosition,
+        constraint = DlmmPosition::from_bytes(&position.try_borrow_data()?)?.lb_pair() == lb_pair.key() @ FeeRoutingError::InvalidDlmmPosition,
+        constraint = DlmmPosition::from_bytes(&position.try_borrow_data()?)?.owner() == fee_vault.key() @ FeeRoutingError::InvalidPositionOwner
+    )]
+    pub position: UncheckedAccount<'info>,
 
-    #[account(mut)]
-    pub position: Account<'info, Position>,
+    /// CHECK: Registration of `position` with the vault, from `register_vault_position`,
+    /// checked with `VaultPositions::require_registered`
+    #[account(
+        seeds = [b"vault_position", fee_vault.key().as_ref(), position.key().as_ref()],
+        bump
+    )]
+    pub vault_position: UncheckedAccount<'info>,
 
-    #[account(mut)]
-    pub lb_pair: Account<'info, LbPair>,
+    /// CHECK: DLMM `LbPair`, owned by `dlmm_program` and read with `load_account`
+    #[account(
+        mut,
+        address = fee_vault.dlmm_pool @ FeeRoutingError::InvalidDlmmPool,
+        owner = dlmm_program.key() @ FeeRoutingError::InvalidDlmmPool
+    )]
+    pub lb_pair: UncheckedAccount<'info>,
 
     #[account(
         mut,
         associated_token::mint = base_mint,
-        associated_token::authority = fee_vault
+        associated_token::authority = fee_vault,
+        associated_token::token_program = base_token_program
     )]
-    pub base_token_account: Account<'info, TokenAccount>,
+    pub base_token_account: InterfaceAccount<'info, TokenAccount>,
 
     #[account(
         mut,
         associated_token::mint = quote_mint,
-        associated_token::authority = fee_vault
+        associated_token::authority = fee_vault,
+        associated_token::token_program = quote_token_program
+    )]
+    pub quote_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        address = load_account::<LbPair>(&lb_pair)?.reserve_x @ FeeRoutingError::InvalidDlmmReserve
     )]
-    pub quote_token_account: Account<'info, TokenAccount>,
+    pub reserve_x: InterfaceAccount<'info, TokenAccount>,
 
-    #[account(mut)]
-    pub reserve_x: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        address = load_account::<LbPair>(&lb_pair)?.reserve_y @ FeeRoutingError::InvalidDlmmReserve
+    )]
+    pub reserve_y: InterfaceAccount<'info, TokenAccount>,
 
-    #[account(mut)]
-    pub reserve_y: Account<'info, TokenAccount>,
+    #[account(
+        address = fee_vault.base_mint @ FeeRoutingError::InvalidDlmmMint,
+        constraint = base_mint.key() == load_account::<LbPair>(&lb_pair)?.token_x_mint @ FeeRoutingError::InvalidDlmmMint
+    )]
+    pub base_mint: InterfaceAccount<'info, Mint>,
 
-    pub base_mint: Account<'info, Mint>,
-    pub quote_mint: Account<'info, Mint>,
+    #[account(
+        address = fee_vault.quote_mint @ FeeRoutingError::InvalidDlmmMint,
+        constraint = quote_mint.key() == load_account::<LbPair>(&lb_pair)?.token_y_mint @ FeeRoutingError::InvalidDlmmMint
+    )]
+    pub quote_mint: InterfaceAccount<'info, Mint>,
 
     pub collector: Signer<'info>,
-    pub token_program: Program<'info, Token>,
 
-    /// CHECK: DLMM program
+    #[account(address = fee_vault.base_token_program @ FeeRoutingError::InvalidTokenProgram)]
+    pub base_token_program: Interface<'info, TokenInterface>,
+
+    #[account(address = fee_vault.quote_token_program @ FeeRoutingError::InvalidTokenProgram)]
+    pub quote_token_program: Interface<'info, TokenInterface>,
+
+    /// CHECK: SPL Memo program, pinned to the known program ID
+    #[account(address = MEMO_PROGRAM_ID @ FeeRoutingError::InvalidMemoProgram)]
+    pub memo_program: UncheckedAccount<'info>,
+
+    /// CHECK: `#[event_cpi]` authority PDA of `dlmm_program`
+    #[account(
+        seeds = [DLMM_EVENT_AUTHORITY_SEED],
+        bump,
+        seeds::program = dlmm_program.key()
+    )]
+    pub event_authority: UncheckedAccount<'info>,
+
+    /// CHECK: DLMM program the CPI goes to, checked against the program config allowlist
     pub dlmm_program: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        constraint = program_config.allows_dlmm_program(&dlmm_program.key()) @ FeeRoutingError::DlmmProgramNotAllowed
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+
+    /// Treasury ATA for the base mint, required while the protocol fee is non-zero
+    #[account(
+        mut,
+        associated_token::mint = base_mint,
+        associated_token::authority = program_config.treasury,
+        associated_token::token_program = base_token_program
+    )]
+    pub treasury_base_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Treasury ATA for the quote mint, required while the protocol fee is non-zero
+    #[account(
+        mut,
+        associated_token::mint = quote_mint,
+        associated_token::authority = program_config.treasury,
+        associated_token::token_program = quote_token_program
+    )]
+    pub treasury_quote_account: Option<InterfaceAccount<'info, TokenAccount>>,
+}
+
+/// Shared accounts for collecting several positions of one pool. Each position,
+/// followed by its `VaultPosition` and bin arrays, is passed as `remaining_accounts`.
+#[derive(Accounts)]
+pub struct CollectManyPositionFees<'info> {
+    #[account(
+        mut,
+        seeds = [b"fee_vault", lb_pair.key().as_ref(), fee_vault.creator_seed()],
+        bump = fee_vault.bump
+    )]
+    pub fee_vault: Account<'info, FeeVault>,
+
+    /// CHECK: DLMM `LbPair`, owned by `dlmm_program` and read with `load_account`
+    #[account(
+        mut,
+        address = fee_vault.dlmm_pool @ FeeRoutingError::InvalidDlmmPool,
+        owner = dlmm_program.key() @ FeeRoutingError::InvalidDlmmPool
+    )]
+    pub lb_pair: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        associated_token::mint = base_mint,
+        associated_token::authority = fee_vault,
+        associated_token::token_program = base_token_program
+    )]
+    pub base_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        associated_token::mint = quote_mint,
+        associated_token::authority = fee_vault,
+        associated_token::token_program = quote_token_program
+    )]
+    pub quote_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        address = load_account::<LbPair>(&lb_pair)?.reserve_x @ FeeRoutingError::InvalidDlmmReserve
+    )]
+    pub reserve_x: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        address = load_account::<LbPair>(&lb_pair)?.reserve_y @ FeeRoutingError::InvalidDlmmReserve
+    )]
+    pub reserve_y: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        address = fee_vault.base_mint @ FeeRoutingError::InvalidDlmmMint,
+        constraint = base_mint.key() == load_account::<LbPair>(&lb_pair)?.token_x_mint @ FeeRoutingError::InvalidDlmmMint
+    )]
+    pub base_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        address = fee_vault.quote_mint @ FeeRoutingError::InvalidDlmmMint,
+        constraint = quote_mint.key() == load_account::<LbPair>(&lb_pair)?.token_y_mint @ FeeRoutingError::InvalidDlmmMint
+    )]
+    pub quote_mint: InterfaceAccount<'info, Mint>,
+
+    pub collector: Signer<'info>,
+
+    #[account(address = fee_vault.base_token_program @ FeeRoutingError::InvalidTokenProgram)]
+    pub base_token_program: Interface<'info, TokenInterface>,
+
+    #[account(address = fee_vault.quote_token_program @ FeeRoutingError::InvalidTokenProgram)]
+    pub quote_token_program: Interface<'info, TokenInterface>,
+
+    /// CHECK: SPL Memo program, pinned to the known program ID
+    #[account(address = MEMO_PROGRAM_ID @ FeeRoutingError::InvalidMemoProgram)]
+    pub memo_program: UncheckedAccount<'info>,
+
+    /// CHECK: `#[event_cpi]` authority PDA of `dlmm_program`
+    #[account(
+        seeds = [DLMM_EVENT_AUTHORITY_SEED],
+        bump,
+        seeds::program = dlmm_program.key()
+    )]
+    pub event_authority: UncheckedAccount<'info>,
+
+    /// CHECK: DLMM program the CPI goes to, checked against the program config allowlist
+    pub dlmm_program: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        constraint = program_config.allows_dlmm_program(&dlmm_program.key()) @ FeeRoutingError::DlmmProgramNotAllowed
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+
+    /// Treasury ATA for the base mint, required while the protocol fee is non-zero
+    #[account(
+        mut,
+        associated_token::mint = base_mint,
+        associated_token::authority = program_config.treasury,
+        associated_token::token_program = base_token_program
+    )]
+    pub treasury_base_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Treasury ATA for the quote mint, required while the protocol fee is non-zero
+    #[account(
+        mut,
+        associated_token::mint = quote_mint,
+        associated_token::authority = program_config.treasury,
+        associated_token::token_program = quote_token_program
+    )]
+    pub treasury_quote_account: Option<InterfaceAccount<'info, TokenAccount>>,
+}
+
+#[derive(Accounts)]
+pub struct CollectProtocolFees<'info> {
+    #[account(
+        mut,
+        seeds = [b"fee_vault", lb_pair.key().as_ref(), fee_vault.creator_seed()],
+        bump = fee_vault.bump
+    )]
+    pub fee_vault: Account<'info, FeeVault>,
+
+    /// CHECK: DLMM `LbPair`, owned by `dlmm_program` and read with `load_account`
+    #[account(
+        mut,
+        address = fee_vault.dlmm_pool @ FeeRoutingError::InvalidDlmmPool,
+        owner = dlmm_program.key() @ FeeRoutingError::InvalidDlmmPool
+    )]
+    pub lb_pair: UncheckedAccount<'info>,
+
+    /// CHECK: DLMM `ClaimFeeOperator` of the vault PDA, which the DLMM admin has to create
+    #[account(
+        seeds = [CLAIM_FEE_OPERATOR_SEED, fee_vault.key().as_ref()],
+        bump,
+        seeds::program = dlmm_program.key(),
+        owner = dlmm_program.key() @ FeeRoutingError::InvalidProtocolFeeOwner,
+        constraint = load_account::<ClaimFeeOperator>(&claim_fee_operator)?.operator == fee_vault.key() @ FeeRoutingError::InvalidProtocolFeeOwner
+    )]
+    pub claim_fee_operator: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        associated_token::mint = base_mint,
+        associated_token::authority = fee_vault,
+        associated_token::token_program = base_token_program
+    )]
+    pub base_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        associated_token::mint = quote_mint,
+        associated_token::authority = fee_vault,
+        associated_token::token_program = quote_token_program
+    )]
+    pub quote_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        address = load_account::<LbPair>(&lb_pair)?.reserve_x @ FeeRoutingError::InvalidDlmmReserve
+    )]
+    pub reserve_x: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        address = load_account::<LbPair>(&lb_pair)?.reserve_y @ FeeRoutingError::InvalidDlmmReserve
+    )]
+    pub reserve_y: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        address = fee_vault.base_mint @ FeeRoutingError::InvalidDlmmMint,
+        constraint = base_mint.key() == load_account::<LbPair>(&lb_pair)?.token_x_mint @ FeeRoutingError::InvalidDlmmMint
+    )]
+    pub base_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        address = fee_vault.quote_mint @ FeeRoutingError::InvalidDlmmMint,
+        constraint = quote_mint.key() == load_account::<LbPair>(&lb_pair)?.token_y_mint @ FeeRoutingError::InvalidDlmmMint
+    )]
+    pub quote_mint: InterfaceAccount<'info, Mint>,
+
+    pub collector: Signer<'info>,
+
+    #[account(address = fee_vault.base_token_program @ FeeRoutingError::InvalidTokenProgram)]
+    pub base_token_program: Interface<'info, TokenInterface>,
+
+    #[account(address = fee_vault.quote_token_program @ FeeRoutingError::InvalidTokenProgram)]
+    pub quote_token_program: Interface<'info, TokenInterface>,
+
+    /// CHECK: SPL Memo program, pinned to the known program ID
+    #[account(address = MEMO_PROGRAM_ID @ FeeRoutingError::InvalidMemoProgram)]
+    pub memo_program: UncheckedAccount<'info>,
+
+    /// CHECK: DLMM program the CPI goes to, checked against the program config allowlist
+    pub dlmm_program: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        constraint = program_config.allows_dlmm_program(&dlmm_program.key()) @ FeeRoutingError::DlmmProgramNotAllowed
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+
+    /// Treasury ATA for the base mint, required while the protocol fee is non-zero
+    #[account(
+        mut,
+        associated_token::mint = base_mint,
+        associated_token::authority = program_config.treasury,
+        associated_token::token_program = base_token_program
+    )]
+    pub treasury_base_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Treasury ATA for the quote mint, required while the protocol fee is non-zero
+    #[account(
+        mut,
+        associated_token::mint = quote_mint,
+        associated_token::authority = program_config.treasury,
+        associated_token::token_program = quote_token_program
+    )]
+    pub treasury_quote_account: Option<InterfaceAccount<'info, TokenAccount>>,
 }
 
-use crate::{FeeVault, FeeRoutingError};
\ No newline at end of file
+use crate::{
+    FeeAmounts, FeeVault, FeeRoutingError, ProgramConfig, TransferFees, TransferHooks, VaultPositions,
+};
@@ -0,0 +1,119 @@
+use anchor_lang::prelude::*;
+
+/// Most DLMM program IDs the allowlist can hold
+pub const MAX_ALLOWED_DLMM_PROGRAMS: usize = 4;
+
+/// Fee vaults listed on each `VaultRegistry` page
+pub const VAULTS_PER_PAGE: u64 = 100;
+
+/// Settings an admin can change on the `ProgramConfig` singleton
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq)]
+pub struct ProgramConfigParams {
+    pub admin: Pubkey,
+    pub protocol_fee_bps: u16,
+    pub allowed_dlmm_programs: Vec<Pubkey>,
+    /// `config_delay` new fee vaults start with
+    pub default_config_delay: i64,
+    /// Owner of the ATAs the protocol fee is sent to
+    pub treasury: Pubkey,
+}
+
+/// Program-wide policy from the `ProgramConfig` singleton, and the registry of
+/// every fee vault
+pub struct ProgramPolicy;
+
+impl ProgramPolicy {
+    pub fn validate(params: &ProgramConfigParams) -> Result<()> {
+        require!(
+            params.protocol_fee_bps <= MAX_PROTOCOL_FEE_BPS,
+            FeeRoutingError::InvalidProgramConfig
+        );
+        require!(
+            params.allowed_dlmm_programs.len() <= MAX_ALLOWED_DLMM_PROGRAMS,
+            FeeRoutingError::InvalidProgramConfig
+        );
+        ConfigTimelock::validate_delay(params.default_config_delay)?;
+        Ok(())
+    }
+
+    pub fn apply(program_config: &mut ProgramConfig, params: ProgramConfigParams) {
+        program_config.admin = params.admin;
+        program_config.protocol_fee_bps = params.protocol_fee_bps;
+        program_config.allowed_dlmm_programs = params.allowed_dlmm_programs;
+        program_config.default_config_delay = params.default_config_delay;
+        program_config.treasury = params.treasury;
+    }
+
+    /// Append a new fee vault to the current registry page
+    pub fn register(
+        program_config: &mut ProgramConfig,
+        registry: &mut VaultRegistry,
+        registry_bump: u8,
+        fee_vault: Pubkey,
+    ) -> Result<()> {
+        let page = program_config.current_registry_page();
+        if registry.vaults.is_empty() {
+            // First vault on a page that `init_if_needed` just created
+            registry.page = page;
+            registry.bump = registry_bump;
+        }
+        require!(registry.page == page, FeeRoutingError::InvalidVaultRegistry);
+
+        registry.vaults.push(fee_vault);
+        program_config.vault_count = program_config.vault_count
+            .checked_add(1)
+            .ok_or(FeeRoutingError::MathOverflow)?;
+        Ok(())
+    }
+
+    /// Take a closed vault off the page that lists it. `vault_count` keeps
+    /// counting it, so later vaults stay on the pages they were put on.
+    pub fn deregister(registry: &mut VaultRegistry, fee_vault: &Pubkey) -> Result<()> {
+        let index = registry.vaults
+            .iter()
+            .position(|vault| vault == fee_vault)
+            .ok_or(FeeRoutingError::InvalidVaultRegistry)?;
+        registry.vaults.remove(index);
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct InitializeProgramConfig<'info> {
+    #[account(
+        init,
+        payer = admin,
+        space = ProgramConfig::LEN,
+        seeds = [b"program_config"],
+        bump
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+
+    /// Only the program's upgrade authority can create the config
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()) @ FeeRoutingError::Unauthorized)]
+    pub program: Program<'info, MeteoraFeeRouting>,
+
+    #[account(constraint = program_data.upgrade_authority_address == Some(admin.key()) @ FeeRoutingError::Unauthorized)]
+    pub program_data: Account<'info, ProgramData>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateProgramConfig<'info> {
+    #[account(
+        mut,
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        has_one = admin
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+
+    pub admin: Signer<'info>,
+}
+
+use crate::program::MeteoraFeeRouting;
+use crate::{ConfigTimelock, FeeRoutingError, ProgramConfig, VaultRegistry, MAX_PROTOCOL_FEE_BPS};
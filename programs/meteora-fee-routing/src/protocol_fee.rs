@@ -0,0 +1,97 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+/// Hard cap on `ProgramConfig.protocol_fee_bps` (20%)
+pub const MAX_PROTOCOL_FEE_BPS: u16 = 2_000;
+
+/// Protocol fee skimmed off collected LP and protocol fees before they
+/// become routable, and sent to the treasury's ATAs
+pub struct ProtocolFee;
+
+impl ProtocolFee {
+    /// Protocol fee owed on `amount` at `bps`, rounded down
+    pub fn on(amount: u64, bps: u16) -> Result<u64> {
+        let fee = (amount as u128)
+            .checked_mul(bps as u128)
+            .ok_or(FeeRoutingError::MathOverflow)?
+            / BPS_DENOMINATOR as u128;
+        Ok(fee as u64)
+    }
+
+    /// Send the protocol fee on a collection to the treasury, add it to the
+    /// vault's skim totals and return the `(base, quote)` left to route.
+    ///
+    /// The treasury accounts may be omitted while the protocol fee is zero.
+    #[allow(clippy::too_many_arguments)]
+    pub fn skim<'info>(
+        fee_vault: &mut Account<'info, FeeVault>,
+        program_config: &ProgramConfig,
+        collected: &FeeAmounts,
+        (base_token_account, quote_token_account): (&InterfaceAccount<'info, TokenAccount>, &InterfaceAccount<'info, TokenAccount>),
+        (treasury_base_account, treasury_quote_account): (
+            &Option<InterfaceAccount<'info, TokenAccount>>,
+            &Option<InterfaceAccount<'info, TokenAccount>>,
+        ),
+        (base_mint, quote_mint): (&InterfaceAccount<'info, Mint>, &InterfaceAccount<'info, Mint>),
+        (base_token_program, quote_token_program): (&Interface<'info, TokenInterface>, &Interface<'info, TokenInterface>),
+        hook_accounts: &[AccountInfo<'info>],
+    ) -> Result<(u64, u64)> {
+        let base_fee = Self::on(collected.base, program_config.protocol_fee_bps)?;
+        let quote_fee = Self::on(collected.quote, program_config.protocol_fee_bps)?;
+        if base_fee == 0 && quote_fee == 0 {
+            return Ok((collected.base, collected.quote));
+        }
+
+        let treasury_base_account = treasury_base_account
+            .as_ref()
+            .ok_or(FeeRoutingError::MissingTreasuryAccount)?;
+        let treasury_quote_account = treasury_quote_account
+            .as_ref()
+            .ok_or(FeeRoutingError::MissingTreasuryAccount)?;
+
+        let base_transfer_fee = transfer_from_vault(
+            base_token_program,
+            fee_vault,
+            base_mint,
+            base_token_account.to_account_info(),
+            treasury_base_account.to_account_info(),
+            base_fee,
+            hook_accounts,
+        )?;
+        let quote_transfer_fee = transfer_from_vault(
+            quote_token_program,
+            fee_vault,
+            quote_mint,
+            quote_token_account.to_account_info(),
+            treasury_quote_account.to_account_info(),
+            quote_fee,
+            hook_accounts,
+        )?;
+
+        fee_vault.total_skimmed_base = fee_vault.total_skimmed_base
+            .checked_add(base_fee)
+            .ok_or(FeeRoutingError::MathOverflow)?;
+        fee_vault.total_skimmed_quote = fee_vault.total_skimmed_quote
+            .checked_add(quote_fee)
+            .ok_or(FeeRoutingError::MathOverflow)?;
+
+        emit!(ProtocolFeeSkimmed {
+            fee_vault: fee_vault.key(),
+            dlmm_pool: fee_vault.dlmm_pool,
+            treasury: program_config.treasury,
+            protocol_fee_bps: program_config.protocol_fee_bps,
+            base_amount: base_fee,
+            quote_amount: quote_fee,
+            base_transfer_fee,
+            quote_transfer_fee,
+            total_skimmed_base: fee_vault.total_skimmed_base,
+            total_skimmed_quote: fee_vault.total_skimmed_quote,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok((collected.base - base_fee, collected.quote - quote_fee))
+    }
+}
+
+use crate::events::ProtocolFeeSkimmed;
+use crate::{transfer_from_vault, FeeAmounts, FeeRoutingError, FeeVault, ProgramConfig, BPS_DENOMINATOR};
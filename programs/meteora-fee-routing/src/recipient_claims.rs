@@ -0,0 +1,265 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+use crate::fee_distribution::transfer_from_vault;
+
+/// Scaling factor applied to the cumulative fee-per-share indexes
+pub const FEE_PER_SHARE_PRECISION: u128 = 1_000_000_000_000;
+
+/// Pull-based fee accounting: collections bump a per-share index on the vault,
+/// recipients settle against it and claim whenever they like
+pub struct PullClaims;
+
+impl PullClaims {
+    /// Fold newly collected fees into the vault's cumulative fee-per-share index.
+    ///
+    /// Only what the index hands out is reserved. The rounding remainder, and
+    /// fees collected while no shares are outstanding, stay routable through
+    /// the push-based distribution instructions.
+    pub fn accrue(fee_vault: &mut FeeVault, base_collected: u64, quote_collected: u64) -> Result<()> {
+        if fee_vault.total_shares == 0 {
+            return Ok(());
+        }
+
+        let total_shares = fee_vault.total_shares as u128;
+        let base_delta = Self::index_delta(base_collected, total_shares)?;
+        let quote_delta = Self::index_delta(quote_collected, total_shares)?;
+        fee_vault.fee_per_share_base = fee_vault.fee_per_share_base
+            .checked_add(base_delta)
+            .ok_or(FeeRoutingError::MathOverflow)?;
+        fee_vault.fee_per_share_quote = fee_vault.fee_per_share_quote
+            .checked_add(quote_delta)
+            .ok_or(FeeRoutingError::MathOverflow)?;
+
+        fee_vault.reserved_base = fee_vault.reserved_base
+            .checked_add(Self::accrued(fee_vault.total_shares, base_delta, 0)?)
+            .ok_or(FeeRoutingError::MathOverflow)?;
+        fee_vault.reserved_quote = fee_vault.reserved_quote
+            .checked_add(Self::accrued(fee_vault.total_shares, quote_delta, 0)?)
+            .ok_or(FeeRoutingError::MathOverflow)?;
+
+        Ok(())
+    }
+
+    /// Bring a recipient position up to date with the vault's indexes
+    pub fn settle(fee_vault: &FeeVault, position: &mut RecipientPosition) -> Result<()> {
+        let base_accrued = Self::accrued(
+            position.shares,
+            fee_vault.fee_per_share_base,
+            position.fee_per_share_base_checkpoint,
+        )?;
+        let quote_accrued = Self::accrued(
+            position.shares,
+            fee_vault.fee_per_share_quote,
+            position.fee_per_share_quote_checkpoint,
+        )?;
+
+        position.pending_base = position.pending_base
+            .checked_add(base_accrued)
+            .ok_or(FeeRoutingError::MathOverflow)?;
+        position.pending_quote = position.pending_quote
+            .checked_add(quote_accrued)
+            .ok_or(FeeRoutingError::MathOverflow)?;
+        position.fee_per_share_base_checkpoint = fee_vault.fee_per_share_base;
+        position.fee_per_share_quote_checkpoint = fee_vault.fee_per_share_quote;
+
+        Ok(())
+    }
+
+    /// Settle a position and move it to a new share count
+    pub fn set_shares(fee_vault: &mut FeeVault, position: &mut RecipientPosition, shares: u64) -> Result<()> {
+        Self::settle(fee_vault, position)?;
+
+        fee_vault.total_shares = fee_vault.total_shares
+            .checked_sub(position.shares)
+            .and_then(|total| total.checked_add(shares))
+            .ok_or(FeeRoutingError::MathOverflow)?;
+        position.shares = shares;
+
+        Ok(())
+    }
+
+    /// Take a position's shares away and stop counting it, once it has claimed
+    /// everything it accrued. With no positions left nobody is owed anything,
+    /// so whatever per-position rounding left in the reserve is released.
+    pub fn close(fee_vault: &mut FeeVault, position: &mut RecipientPosition) -> Result<()> {
+        Self::set_shares(fee_vault, position, 0)?;
+        require!(
+            position.pending_base == 0 && position.pending_quote == 0,
+            FeeRoutingError::RecipientPositionNotEmpty
+        );
+
+        fee_vault.recipient_count = fee_vault.recipient_count
+            .checked_sub(1)
+            .ok_or(FeeRoutingError::MathOverflow)?;
+        if fee_vault.recipient_count == 0 {
+            fee_vault.reserved_base = 0;
+            fee_vault.reserved_quote = 0;
+        }
+
+        Ok(())
+    }
+
+    /// Pay out everything a recipient position has accrued (permissionless).
+    ///
+    /// Accruals are paid as-is out of the reserve, so for Token-2022 mints with a
+    /// transfer fee the recipient receives less; the returned amounts say how much.
+    /// `hook_accounts` are forwarded to transfers of hooked Token-2022 mints.
+    pub fn claim<'info>(accounts: &mut Claim<'info>, hook_accounts: &[AccountInfo<'info>]) -> Result<FeeAmounts> {
+        Self::settle(&accounts.fee_vault, &mut accounts.recipient_position)?;
+
+        let base_claimed = accounts.recipient_position.pending_base;
+        let quote_claimed = accounts.recipient_position.pending_quote;
+
+        let base_transfer_fee = transfer_from_vault(
+            &accounts.base_token_program,
+            &accounts.fee_vault,
+            &accounts.base_mint,
+            accounts.base_token_account.to_account_info(),
+            accounts.recipient_base_account.to_account_info(),
+            base_claimed,
+            hook_accounts,
+        )?;
+        let quote_transfer_fee = transfer_from_vault(
+            &accounts.quote_token_program,
+            &accounts.fee_vault,
+            &accounts.quote_mint,
+            accounts.quote_token_account.to_account_info(),
+            accounts.recipient_quote_account.to_account_info(),
+            quote_claimed,
+            hook_accounts,
+        )?;
+
+        let position = &mut accounts.recipient_position;
+        position.pending_base = 0;
+        position.pending_quote = 0;
+        position.total_claimed_base = position.total_claimed_base
+            .checked_add(base_claimed)
+            .ok_or(FeeRoutingError::MathOverflow)?;
+        position.total_claimed_quote = position.total_claimed_quote
+            .checked_add(quote_claimed)
+            .ok_or(FeeRoutingError::MathOverflow)?;
+
+        let fee_vault = &mut accounts.fee_vault;
+        fee_vault.reserved_base = fee_vault.reserved_base.saturating_sub(base_claimed);
+        fee_vault.reserved_quote = fee_vault.reserved_quote.saturating_sub(quote_claimed);
+
+        Ok(FeeAmounts {
+            base: base_claimed.saturating_sub(base_transfer_fee),
+            quote: quote_claimed.saturating_sub(quote_transfer_fee),
+            base_transfer_fee,
+            quote_transfer_fee,
+        })
+    }
+
+    fn index_delta(collected: u64, total_shares: u128) -> Result<u128> {
+        Ok((collected as u128)
+            .checked_mul(FEE_PER_SHARE_PRECISION)
+            .ok_or(FeeRoutingError::MathOverflow)?
+            / total_shares)
+    }
+
+    fn accrued(shares: u64, index: u128, checkpoint: u128) -> Result<u64> {
+        let accrued = (shares as u128)
+            .checked_mul(index.saturating_sub(checkpoint))
+            .ok_or(FeeRoutingError::MathOverflow)?
+            / FEE_PER_SHARE_PRECISION;
+        u64::try_from(accrued).map_err(|_| error!(FeeRoutingError::MathOverflow))
+    }
+}
+
+#[derive(Accounts)]
+pub struct Claim<'info> {
+    #[account(
+        mut,
+        seeds = [b"fee_vault", fee_vault.dlmm_pool.as_ref(), fee_vault.creator_seed()],
+        bump = fee_vault.bump
+    )]
+    pub fee_vault: Account<'info, FeeVault>,
+
+    #[account(
+        mut,
+        seeds = [b"recipient_position", fee_vault.key().as_ref(), recipient.key().as_ref()],
+        bump = recipient_position.bump,
+        has_one = fee_vault,
+        has_one = recipient
+    )]
+    pub recipient_position: Account<'info, RecipientPosition>,
+
+    /// CHECK: Recipient the position pays out to
+    pub recipient: UncheckedAccount<'info>,
+
+    #[account(address = fee_vault.base_mint @ FeeRoutingError::InvalidDlmmMint)]
+    pub base_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(address = fee_vault.quote_mint @ FeeRoutingError::InvalidDlmmMint)]
+    pub quote_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        associated_token::mint = base_mint,
+        associated_token::authority = fee_vault,
+        associated_token::token_program = base_token_program
+    )]
+    pub base_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        associated_token::mint = quote_mint,
+        associated_token::authority = fee_vault,
+        associated_token::token_program = quote_token_program
+    )]
+    pub quote_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = base_mint,
+        token::authority = recipient,
+        token::token_program = base_token_program
+    )]
+    pub recipient_base_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = quote_mint,
+        token::authority = recipient,
+        token::token_program = quote_token_program
+    )]
+    pub recipient_quote_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub claimer: Signer<'info>,
+
+    #[account(address = fee_vault.base_token_program @ FeeRoutingError::InvalidTokenProgram)]
+    pub base_token_program: Interface<'info, TokenInterface>,
+
+    #[account(address = fee_vault.quote_token_program @ FeeRoutingError::InvalidTokenProgram)]
+    pub quote_token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct CloseRecipientPosition<'info> {
+    #[account(
+        mut,
+        seeds = [b"recipient_position", fee_vault.key().as_ref(), recipient_position.recipient.as_ref()],
+        bump = recipient_position.bump,
+        has_one = fee_vault,
+        close = rent_receiver
+    )]
+    pub recipient_position: Account<'info, RecipientPosition>,
+
+    #[account(
+        mut,
+        seeds = [b"fee_vault", fee_vault.dlmm_pool.as_ref(), fee_vault.creator_seed()],
+        bump = fee_vault.bump,
+        constraint = fee_vault.has_role(Role::ConfigManager, &authority.key()) @ FeeRoutingError::MissingRole
+    )]
+    pub fee_vault: Account<'info, FeeVault>,
+
+    /// CHECK: Vault authority or config manager, approved through `MultisigApproval::authorize`
+    pub authority: UncheckedAccount<'info>,
+
+    /// CHECK: Receives the position's rent
+    #[account(mut)]
+    pub rent_receiver: UncheckedAccount<'info>,
+}
+
+use crate::{FeeAmounts, FeeVault, FeeRoutingError, RecipientPosition, Role};
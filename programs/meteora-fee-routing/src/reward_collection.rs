@@ -0,0 +1,170 @@
+use anchor_lang::prelude::*;
+use anchor_spl::associated_token::get_associated_token_address_with_program_id;
+use anchor_spl::token_interface::TokenAccount;
+use crate::dlmm_integration::meteora_dlmm_types::*;
+use crate::dlmm_integration::cpi_instructions::{self, AccountsType};
+
+/// Accounts per initialized reward slot in `collect_position_rewards` remaining
+/// accounts: `[reward_vault, reward_mint, vault_reward_account, reward_token_program]`
+pub const ACCOUNTS_PER_REWARD: usize = 4;
+
+/// Collection of DLMM farming rewards into vault-owned token accounts
+pub struct RewardCollection;
+
+impl RewardCollection {
+    /// Claim every initialized reward slot of the pool for one position.
+    ///
+    /// `remaining_accounts` must start with the `bin_array_count` bin arrays
+    /// the position spans, then hold one `[reward_vault, reward_mint,
+    /// vault_reward_account, reward_token_program]` group per initialized slot,
+    /// in slot order, followed by any transfer hook accounts for hooked reward
+    /// mints. The token program must own the reward mint, and the vault reward
+    /// account must be the fee vault's ATA for the mint under that program.
+    /// Returns `(reward_index, reward_mint, amount, transfer_fee)` for each
+    /// claimed slot, where `amount` is what landed in the vault.
+    pub fn collect<'info>(
+        ctx: &Context<'_, '_, 'info, 'info, CollectPositionRewards<'info>>,
+        bin_array_count: u8,
+    ) -> Result<Vec<(usize, Pubkey, u64, u64)>> {
+        VaultPositions::require_registered(
+            &ctx.accounts.vault_position,
+            &ctx.accounts.fee_vault.key(),
+            ctx.accounts.position.key,
+        )?;
+        let reward_infos = load_account::<LbPair>(&ctx.accounts.lb_pair)?.reward_infos;
+        let fee_vault = &ctx.accounts.fee_vault;
+        let (min_bin_id, max_bin_id) = PermissionlessInterface::bin_range(&ctx.accounts.position)?;
+        let (bin_arrays, remaining_accounts) = PermissionlessInterface::split_bin_arrays(
+            ctx.remaining_accounts,
+            bin_array_count,
+            (min_bin_id, max_bin_id),
+            &ctx.accounts.dlmm_program.key(),
+        )?;
+
+        let active: Vec<(usize, RewardInfo)> = reward_infos
+            .into_iter()
+            .enumerate()
+            .filter(|(_, info)| info.initialized())
+            .collect();
+        require!(
+            remaining_accounts.len() >= active.len() * ACCOUNTS_PER_REWARD,
+            FeeRoutingError::InvalidRewardAccount
+        );
+        let (reward_accounts, hook_accounts) = remaining_accounts.split_at(active.len() * ACCOUNTS_PER_REWARD);
+
+        let seeds = fee_vault.signer_seeds();
+        let signer = &[&seeds[..]];
+
+        let mut collected = Vec::with_capacity(active.len());
+        for ((index, info), accounts) in active.into_iter().zip(reward_accounts.chunks(ACCOUNTS_PER_REWARD)) {
+            let reward_vault = &accounts[0];
+            let reward_mint = &accounts[1];
+            let reward_token_program = &accounts[3];
+            require_keys_eq!(reward_vault.key(), info.vault, FeeRoutingError::InvalidRewardAccount);
+            require_keys_eq!(reward_mint.key(), info.mint, FeeRoutingError::InvalidRewardAccount);
+            require_keys_eq!(*reward_mint.owner, reward_token_program.key(), FeeRoutingError::InvalidTokenProgram);
+
+            let mut vault_reward_account = InterfaceAccount::<TokenAccount>::try_from(&accounts[2])?;
+            require_keys_eq!(
+                vault_reward_account.key(),
+                get_associated_token_address_with_program_id(&fee_vault.key(), &info.mint, reward_token_program.key),
+                FeeRoutingError::InvalidRewardAccount
+            );
+            let balance_before = vault_reward_account.amount;
+
+            let cpi_accounts = cpi_instructions::ClaimReward2 {
+                lb_pair: ctx.accounts.lb_pair.to_account_info(),
+                position: ctx.accounts.position.to_account_info(),
+                owner: fee_vault.to_account_info(),
+                reward_vault: reward_vault.clone(),
+                reward_mint: reward_mint.clone(),
+                user_token_account: accounts[2].clone(),
+                token_program: reward_token_program.clone(),
+                memo_program: ctx.accounts.memo_program.to_account_info(),
+                event_authority: ctx.accounts.event_authority.to_account_info(),
+            };
+            let (remaining_accounts_info, mut remaining_accounts) = TransferHooks::dlmm_remaining_accounts(
+                &[(AccountsType::TransferHookReward, reward_mint.clone())],
+                hook_accounts,
+            )?;
+            remaining_accounts.extend_from_slice(bin_arrays);
+
+            let cpi_program = ctx.accounts.dlmm_program.to_account_info();
+            let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer)
+                .with_remaining_accounts(remaining_accounts);
+            cpi_instructions::claim_reward2(cpi_ctx, index as u64, min_bin_id, max_bin_id, remaining_accounts_info)?;
+
+            vault_reward_account.reload()?;
+            let amount = vault_reward_account.amount.saturating_sub(balance_before);
+            let transfer_fee = TransferFees::on_receive(reward_mint, amount)?;
+
+            msg!("Reward {} ({}) collected: {}", index, info.mint, amount);
+            collected.push((index, info.mint, amount, transfer_fee));
+        }
+
+        Ok(collected)
+    }
+}
+
+#[derive(Accounts)]
+pub struct CollectPositionRewards<'info> {
+    #[account(
+        mut,
+        seeds = [b"fee_vault", lb_pair.key().as_ref(), fee_vault.creator_seed()],
+        bump = fee_vault.bump
+    )]
+    pub fee_vault: Account<'info, FeeVault>,
+
+    /// CHECK: DLMM `LbPair`, owned by `dlmm_program` and read with `load_account`
+    #[account(
+        mut,
+        address = fee_vault.dlmm_pool @ FeeRoutingError::InvalidDlmmPool,
+        owner = dlmm_program.key() @ FeeRoutingError::InvalidDlmmPool
+    )]
+    pub lb_pair: UncheckedAccount<'info>,
+
+    /// CHECK: DLMM `Position` or `PositionV2`, decoded by discriminator
+    #[account(
+        mut,
+        owner = dlmm_program.key() @ FeeRoutingError::InvalidDlmmPosition,
+        constraint = DlmmPosition::from_bytes(&position.try_borrow_data()?)?.lb_pair() == lb_pair.key() @ FeeRoutingError::InvalidDlmmPosition,
+        constraint = DlmmPosition::from_bytes(&position.try_borrow_data()?)?.owner() == fee_vault.key() @ FeeRoutingError::InvalidPositionOwner
+    )]
+    pub position: UncheckedAccount<'info>,
+
+    /// CHECK: Registration of `position` with the vault, from `register_vault_position`,
+    /// checked with `VaultPositions::require_registered`
+    #[account(
+        seeds = [b"vault_position", fee_vault.key().as_ref(), position.key().as_ref()],
+        bump
+    )]
+    pub vault_position: UncheckedAccount<'info>,
+
+    pub collector: Signer<'info>,
+
+    /// CHECK: SPL Memo program, pinned to the known program ID
+    #[account(address = MEMO_PROGRAM_ID @ FeeRoutingError::InvalidMemoProgram)]
+    pub memo_program: UncheckedAccount<'info>,
+
+    /// CHECK: `#[event_cpi]` authority PDA of `dlmm_program`
+    #[account(
+        seeds = [DLMM_EVENT_AUTHORITY_SEED],
+        bump,
+        seeds::program = dlmm_program.key()
+    )]
+    pub event_authority: UncheckedAccount<'info>,
+
+    /// CHECK: DLMM program the CPI goes to, checked against the program config allowlist
+    pub dlmm_program: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        constraint = program_config.allows_dlmm_program(&dlmm_program.key()) @ FeeRoutingError::DlmmProgramNotAllowed
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+}
+
+use crate::{
+    FeeVault, FeeRoutingError, PermissionlessInterface, ProgramConfig, TransferFees, TransferHooks, VaultPositions,
+};
@@ -0,0 +1,59 @@
+use anchor_lang::prelude::*;
+
+/// Vault roles. The admin is `FeeVault.authority`, moves with `propose_authority` /
+/// `accept_authority`, and implicitly holds every other role.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Role {
+    Admin,
+    /// May call `distribute_fees`
+    Distributor,
+    /// May set the pause flags
+    Guardian,
+    /// May manage the split table and recipient positions
+    ConfigManager,
+}
+
+/// Grants and revokes the delegated roles, one holder per role
+pub struct VaultRoles;
+
+impl VaultRoles {
+    /// Current holder of a role
+    pub fn holder(fee_vault: &FeeVault, role: Role) -> Option<Pubkey> {
+        match role {
+            Role::Admin => Some(fee_vault.authority),
+            Role::Distributor => fee_vault.distributor,
+            Role::Guardian => fee_vault.guardian,
+            Role::ConfigManager => fee_vault.config_manager,
+        }
+    }
+
+    /// Hand a delegated role to `holder`, or revoke it with `None`. Returns the previous holder.
+    pub fn set(fee_vault: &mut FeeVault, role: Role, holder: Option<Pubkey>) -> Result<Option<Pubkey>> {
+        let slot = match role {
+            Role::Admin => return err!(FeeRoutingError::InvalidRole),
+            Role::Distributor => &mut fee_vault.distributor,
+            Role::Guardian => &mut fee_vault.guardian,
+            Role::ConfigManager => &mut fee_vault.config_manager,
+        };
+        Ok(std::mem::replace(slot, holder))
+    }
+
+    /// `set` a role and emit `RoleChanged`
+    pub fn assign(fee_vault: &mut Account<FeeVault>, role: Role, holder: Option<Pubkey>) -> Result<()> {
+        let old_holder = Self::set(fee_vault, role, holder)?;
+
+        emit!(RoleChanged {
+            fee_vault: fee_vault.key(),
+            dlmm_pool: fee_vault.dlmm_pool,
+            role,
+            old_holder,
+            new_holder: holder,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+}
+
+use crate::events::RoleChanged;
+use crate::{FeeVault, FeeRoutingError};
@@ -0,0 +1,85 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_2022::spl_token_2022::{
+    self,
+    extension::{transfer_fee::{TransferFee, TransferFeeConfig}, BaseStateWithExtensions, StateWithExtensions},
+};
+
+/// Base and quote amounts moved by one instruction, and the Token-2022 transfer
+/// fees withheld from them. `base` and `quote` are what the receiving account
+/// ended up with; adding the transfer fee gives what the sender paid out.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct FeeAmounts {
+    pub base: u64,
+    pub quote: u64,
+    pub base_transfer_fee: u64,
+    pub quote_transfer_fee: u64,
+}
+
+impl FeeAmounts {
+    pub fn checked_add(self, other: Self) -> Result<Self> {
+        let add = |a: u64, b: u64| a.checked_add(b).ok_or(error!(FeeRoutingError::MathOverflow));
+        Ok(Self {
+            base: add(self.base, other.base)?,
+            quote: add(self.quote, other.quote)?,
+            base_transfer_fee: add(self.base_transfer_fee, other.base_transfer_fee)?,
+            quote_transfer_fee: add(self.quote_transfer_fee, other.quote_transfer_fee)?,
+        })
+    }
+
+    /// Base amount before the transfer fee was withheld
+    pub fn base_gross(&self) -> Result<u64> {
+        self.base.checked_add(self.base_transfer_fee).ok_or(error!(FeeRoutingError::MathOverflow))
+    }
+
+    /// Quote amount before the transfer fee was withheld
+    pub fn quote_gross(&self) -> Result<u64> {
+        self.quote.checked_add(self.quote_transfer_fee).ok_or(error!(FeeRoutingError::MathOverflow))
+    }
+}
+
+/// Token-2022 `TransferFee` extension math. Mints owned by the classic SPL Token
+/// program, or Token-2022 mints without the extension, never withhold anything.
+pub struct TransferFees;
+
+impl TransferFees {
+    /// Fee withheld in the destination when `amount` is sent from the vault
+    pub fn on_send(mint: &AccountInfo, amount: u64) -> Result<u64> {
+        match Self::epoch_fee(mint)? {
+            Some(fee) => fee.calculate_fee(amount).ok_or(error!(FeeRoutingError::MathOverflow)),
+            None => Ok(0),
+        }
+    }
+
+    /// Fee that was withheld from a transfer that delivered `received` to the vault
+    pub fn on_receive(mint: &AccountInfo, received: u64) -> Result<u64> {
+        match Self::epoch_fee(mint)? {
+            Some(fee) => fee.calculate_inverse_fee(received).ok_or(error!(FeeRoutingError::MathOverflow)),
+            None => Ok(0),
+        }
+    }
+
+    /// Amount the vault has to send for the destination to end up with `net`
+    pub fn gross_for(mint: &AccountInfo, net: u64) -> Result<u64> {
+        match Self::epoch_fee(mint)? {
+            Some(fee) => fee.calculate_pre_fee_amount(net).ok_or(error!(FeeRoutingError::MathOverflow)),
+            None => Ok(net),
+        }
+    }
+
+    /// The transfer fee schedule in force for the current epoch, if the mint has one
+    fn epoch_fee(mint: &AccountInfo) -> Result<Option<TransferFee>> {
+        if *mint.owner != spl_token_2022::ID {
+            return Ok(None);
+        }
+
+        let data = mint.try_borrow_data()?;
+        let state = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&data)?;
+        let Ok(config) = state.get_extension::<TransferFeeConfig>() else {
+            return Ok(None);
+        };
+
+        Ok(Some(*config.get_epoch_fee(Clock::get()?.epoch)))
+    }
+}
+
+use crate::FeeRoutingError;
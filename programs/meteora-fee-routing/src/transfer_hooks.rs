@@ -0,0 +1,100 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_2022::spl_token_2022::{
+    self,
+    extension::{transfer_hook, StateWithExtensions},
+};
+use crate::dlmm_integration::cpi_instructions::{AccountsType, RemainingAccountsInfo, RemainingAccountsSlice};
+
+/// Seed of a hooked mint's `ExtraAccountMetaList` validation account
+const EXTRA_ACCOUNT_METAS_SEED: &[u8] = b"extra-account-metas";
+
+/// SPL discriminator of the transfer hook `Execute` instruction, the first 8
+/// bytes of `sha256("spl-transfer-hook-interface:execute")`
+const EXECUTE_DISCRIMINATOR: [u8; 8] = [105, 37, 101, 197, 75, 251, 102, 26];
+
+/// Token-2022 `TransferHook` support.
+///
+/// Clients resolve the extra accounts each hooked mint needs from its
+/// `ExtraAccountMetaList` and pass them as remaining accounts. Transfers signed
+/// by the vault hand the whole set to the token program, which picks out the
+/// accounts the mint's hook asks for. DLMM collection gets one slice per mint.
+pub struct TransferHooks;
+
+impl TransferHooks {
+    /// Hook program the mint routes transfers through, if any
+    pub fn program_id(mint: &AccountInfo) -> Result<Option<Pubkey>> {
+        if *mint.owner != spl_token_2022::ID {
+            return Ok(None);
+        }
+
+        let data = mint.try_borrow_data()?;
+        let state = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&data)?;
+        Ok(transfer_hook::get_program_id(&state))
+    }
+
+    /// Lay out `hook_accounts` for a DLMM `*2` instruction: one slice per hooked
+    /// mint, carrying only that mint's accounts. Returns the slice table and the
+    /// accounts that go ahead of the bin arrays.
+    ///
+    /// Each hooked mint's accounts must be passed together as `[extra accounts..,
+    /// hook program, validation account]`, the order SPL clients resolve them in.
+    /// The group is found by the mint's validation account, which also gives its size.
+    pub fn dlmm_remaining_accounts<'info>(
+        mints: &[(AccountsType, AccountInfo<'info>)],
+        hook_accounts: &[AccountInfo<'info>],
+    ) -> Result<(RemainingAccountsInfo, Vec<AccountInfo<'info>>)> {
+        let mut info = RemainingAccountsInfo::default();
+        let mut accounts = Vec::new();
+
+        for (accounts_type, mint) in mints {
+            let Some(hook_program) = Self::program_id(mint)? else {
+                continue;
+            };
+            let mint_accounts = Self::mint_accounts(mint.key, &hook_program, hook_accounts)?;
+
+            info.slices.push(RemainingAccountsSlice {
+                accounts_type: *accounts_type,
+                length: u8::try_from(mint_accounts.len())
+                    .map_err(|_| error!(FeeRoutingError::InvalidTransferHookAccounts))?,
+            });
+            accounts.extend_from_slice(mint_accounts);
+        }
+
+        Ok((info, accounts))
+    }
+
+    /// The group of `hook_accounts` ending in `mint`'s validation account
+    fn mint_accounts<'a, 'info>(
+        mint: &Pubkey,
+        hook_program: &Pubkey,
+        hook_accounts: &'a [AccountInfo<'info>],
+    ) -> Result<&'a [AccountInfo<'info>]> {
+        let (validation, _) = Pubkey::find_program_address(&[EXTRA_ACCOUNT_METAS_SEED, mint.as_ref()], hook_program);
+        let end = hook_accounts
+            .iter()
+            .position(|account| *account.key == validation)
+            .ok_or(FeeRoutingError::InvalidTransferHookAccounts)?;
+        require_keys_eq!(*hook_accounts[end].owner, *hook_program, FeeRoutingError::InvalidTransferHookAccounts);
+
+        let extra_accounts = Self::extra_account_count(&hook_accounts[end].try_borrow_data()?)?;
+        let start = end
+            .checked_sub(1 + extra_accounts)
+            .ok_or(FeeRoutingError::InvalidTransferHookAccounts)?;
+        require_keys_eq!(*hook_accounts[end - 1].key, *hook_program, FeeRoutingError::InvalidTransferHookAccounts);
+
+        Ok(&hook_accounts[start..=end])
+    }
+
+    /// Number of extra accounts in an `ExtraAccountMetaList`: its `Execute` TLV
+    /// entry starts with an 8-byte type and a 4-byte length, then the slice's
+    /// `u32` item count
+    fn extra_account_count(data: &[u8]) -> Result<usize> {
+        require!(
+            data.len() >= 16 && data[..8] == EXECUTE_DISCRIMINATOR,
+            FeeRoutingError::InvalidTransferHookAccounts
+        );
+        Ok(u32::from_le_bytes([data[12], data[13], data[14], data[15]]) as usize)
+    }
+}
+
+use crate::FeeRoutingError;
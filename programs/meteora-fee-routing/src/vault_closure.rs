@@ -0,0 +1,138 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{self, CloseAccount, Mint, TokenAccount, TokenInterface};
+
+/// Tears down an emptied fee vault and reclaims the rent of its accounts
+pub struct VaultClosure;
+
+impl VaultClosure {
+    /// Check the vault holds nothing and has no registered positions, recipient
+    /// positions or queued changes left, take it off its registry page, then
+    /// close both vault ATAs and any distribution config or multisig to the
+    /// rent receiver. The `FeeVault` account itself is closed by the accounts
+    /// struct.
+    pub fn close<'info>(accounts: &mut CloseFeeVault<'info>) -> Result<()> {
+        let fee_vault = &accounts.fee_vault;
+        require!(
+            fee_vault.position_count == 0
+                && fee_vault.recipient_count == 0
+                && fee_vault.queued_change_count == 0,
+            FeeRoutingError::VaultHasOpenAccounts
+        );
+        require!(
+            accounts.base_token_account.amount == 0
+                && accounts.quote_token_account.amount == 0
+                && fee_vault.reserved_base == 0
+                && fee_vault.reserved_quote == 0,
+            FeeRoutingError::VaultNotEmpty
+        );
+
+        ProgramPolicy::deregister(&mut accounts.vault_registry, &fee_vault.key())?;
+
+        let seeds = fee_vault.signer_seeds();
+        let signer = &[&seeds[..]];
+
+        for (token_program, token_account) in [
+            (&accounts.base_token_program, &accounts.base_token_account),
+            (&accounts.quote_token_program, &accounts.quote_token_account),
+        ] {
+            token_interface::close_account(CpiContext::new_with_signer(
+                token_program.to_account_info(),
+                CloseAccount {
+                    account: token_account.to_account_info(),
+                    destination: accounts.rent_receiver.to_account_info(),
+                    authority: fee_vault.to_account_info(),
+                },
+                signer,
+            ))?;
+        }
+
+        for child in [&accounts.distribution_config, &accounts.vault_multisig] {
+            Self::close_if_created(child, &accounts.rent_receiver)?;
+        }
+
+        Ok(())
+    }
+
+    /// Close a vault PDA that may never have been created, the way `close = `
+    /// closes an `Account`. The seeds constraint has already pinned its address.
+    fn close_if_created<'info>(account: &AccountInfo<'info>, rent_receiver: &AccountInfo<'info>) -> Result<()> {
+        if *account.owner != crate::ID {
+            return Ok(());
+        }
+
+        let lamports = account.lamports();
+        **rent_receiver.try_borrow_mut_lamports()? = rent_receiver.lamports()
+            .checked_add(lamports)
+            .ok_or(FeeRoutingError::MathOverflow)?;
+        **account.try_borrow_mut_lamports()? = 0;
+        account.assign(&System::id());
+        account.resize(0)?;
+
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct CloseFeeVault<'info> {
+    #[account(
+        mut,
+        seeds = [b"fee_vault", fee_vault.dlmm_pool.as_ref(), fee_vault.creator_seed()],
+        bump = fee_vault.bump,
+        has_one = authority,
+        close = rent_receiver
+    )]
+    pub fee_vault: Account<'info, FeeVault>,
+
+    /// CHECK: Vault authority, approved through `MultisigApproval::authorize`
+    pub authority: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        associated_token::mint = base_mint,
+        associated_token::authority = fee_vault,
+        associated_token::token_program = base_token_program
+    )]
+    pub base_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        associated_token::mint = quote_mint,
+        associated_token::authority = fee_vault,
+        associated_token::token_program = quote_token_program
+    )]
+    pub quote_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(address = fee_vault.base_mint @ FeeRoutingError::InvalidDlmmMint)]
+    pub base_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(address = fee_vault.quote_mint @ FeeRoutingError::InvalidDlmmMint)]
+    pub quote_mint: InterfaceAccount<'info, Mint>,
+
+    /// CHECK: Receives the rent of the vault and every account closed with it
+    #[account(mut)]
+    pub rent_receiver: UncheckedAccount<'info>,
+
+    #[account(address = fee_vault.base_token_program @ FeeRoutingError::InvalidTokenProgram)]
+    pub base_token_program: Interface<'info, TokenInterface>,
+
+    #[account(address = fee_vault.quote_token_program @ FeeRoutingError::InvalidTokenProgram)]
+    pub quote_token_program: Interface<'info, TokenInterface>,
+
+    /// CHECK: The vault's `DistributionConfig` PDA, closed here if it was created
+    #[account(mut, seeds = [b"distribution_config", fee_vault.key().as_ref()], bump)]
+    pub distribution_config: UncheckedAccount<'info>,
+
+    /// CHECK: The vault's `VaultMultisig` PDA, closed here if it was created
+    #[account(mut, seeds = [b"vault_multisig", fee_vault.key().as_ref()], bump)]
+    pub vault_multisig: UncheckedAccount<'info>,
+
+    /// Registry page listing the vault
+    #[account(
+        mut,
+        seeds = [b"vault_registry".as_ref(), &vault_registry.page.to_le_bytes()],
+        bump = vault_registry.bump
+    )]
+    pub vault_registry: Account<'info, VaultRegistry>,
+}
+
+use crate::{FeeVault, FeeRoutingError, ProgramPolicy, VaultRegistry};
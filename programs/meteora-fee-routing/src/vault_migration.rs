@@ -0,0 +1,174 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{self, Transfer};
+use anchor_lang::Discriminator;
+use crate::dlmm_integration::meteora_dlmm_types::NUM_REWARDS;
+
+/// A fee vault as the first release wrote it, at `[b"fee_vault", dlmm_pool]`.
+/// `migrate_fee_vault` grows it in place to the current layout.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct FeeVaultV0 {
+    pub authority: Pubkey,
+    pub dlmm_pool: Pubkey,
+    pub base_mint: Pubkey,
+    pub quote_mint: Pubkey,
+    pub bump: u8,
+    pub total_collected_base: u64,
+    pub total_collected_quote: u64,
+    pub creation_time: i64,
+}
+
+impl FeeVaultV0 {
+    pub const LEN: usize = 8 + // discriminator
+        32 + // authority
+        32 + // dlmm_pool
+        32 + // base_mint
+        32 + // quote_mint
+        1 +  // bump
+        8 +  // total_collected_base
+        8 +  // total_collected_quote
+        8;   // creation_time
+
+    /// Decode a v0 vault, which carries the same discriminator as `FeeVault`
+    pub fn from_data(data: &[u8]) -> Result<Self> {
+        require!(
+            data.len() == Self::LEN && data[..8] == *FeeVault::DISCRIMINATOR,
+            FeeRoutingError::InvalidFeeVaultVersion
+        );
+        Self::deserialize(&mut &data[8..]).map_err(|_| error!(FeeRoutingError::InvalidFeeVaultVersion))
+    }
+
+    /// The vault in the current layout, keyed by `creator` (`Pubkey::default()`
+    /// to keep the v0 address), with its totals
+    /// carried over and every field added since at its default. The first
+    /// release only supported SPL Token mints, which withhold no transfer fee,
+    /// so the gross totals equal the net ones.
+    pub fn into_current(self, creator: Pubkey, bump: u8, config_delay: i64) -> FeeVault {
+        FeeVault {
+            authority: self.authority,
+            dlmm_pool: self.dlmm_pool,
+            base_mint: self.base_mint,
+            quote_mint: self.quote_mint,
+            bump,
+            total_collected_base: self.total_collected_base,
+            total_collected_quote: self.total_collected_quote,
+            creation_time: self.creation_time,
+            total_shares: 0,
+            fee_per_share_base: 0,
+            fee_per_share_quote: 0,
+            reserved_base: 0,
+            reserved_quote: 0,
+            distributor: None,
+            total_protocol_fees_base: 0,
+            total_protocol_fees_quote: 0,
+            reward_mints: [Pubkey::default(); NUM_REWARDS],
+            total_collected_rewards: [0; NUM_REWARDS],
+            base_token_program: anchor_spl::token::ID,
+            quote_token_program: anchor_spl::token::ID,
+            total_collected_gross_base: self.total_collected_base,
+            total_collected_gross_quote: self.total_collected_quote,
+            pending_authority: None,
+            config_delay,
+            next_change_id: 0,
+            paused: 0,
+            guardian: None,
+            config_manager: None,
+            total_skimmed_base: 0,
+            total_skimmed_quote: 0,
+            creator,
+            version: FEE_VAULT_VERSION,
+            position_count: 0,
+            recipient_count: 0,
+            queued_change_count: 0,
+            reserved: [0; FEE_VAULT_RESERVED],
+        }
+    }
+}
+
+/// In-place upgrades of v0 fee vaults to the current layout
+pub struct FeeVaultMigration;
+
+impl FeeVaultMigration {
+    /// Grow a v0 vault to `FeeVault::LEN`, with `payer` topping up its rent,
+    /// and rewrite it in the current layout. The vault stays at
+    /// `[b"fee_vault", dlmm_pool]` with its creator unset, so it keeps signing
+    /// for the token accounts, positions and protocol fees it already owns.
+    pub fn migrate(accounts: &MigrateFeeVault) -> Result<FeeVault> {
+        let info = accounts.fee_vault.to_account_info();
+        require!(info.data_len() < FeeVault::LEN, FeeRoutingError::FeeVaultAlreadyMigrated);
+        let legacy = FeeVaultV0::from_data(&info.try_borrow_data()?)?;
+        require_keys_eq!(legacy.authority, accounts.authority.key(), FeeRoutingError::Unauthorized);
+
+        let bump = legacy.bump;
+        let fee_vault = legacy.into_current(
+            Pubkey::default(),
+            bump,
+            accounts.program_config.default_config_delay,
+        );
+
+        let rent = Rent::get()?
+            .minimum_balance(FeeVault::LEN)
+            .saturating_sub(info.lamports());
+        if rent > 0 {
+            system_program::transfer(
+                CpiContext::new(
+                    accounts.system_program.to_account_info(),
+                    Transfer {
+                        from: accounts.payer.to_account_info(),
+                        to: info.clone(),
+                    },
+                ),
+                rent,
+            )?;
+        }
+
+        info.resize(FeeVault::LEN)?;
+        fee_vault.try_serialize(&mut &mut info.try_borrow_mut_data()?[..])?;
+
+        Ok(fee_vault)
+    }
+}
+
+#[derive(Accounts)]
+pub struct MigrateFeeVault<'info> {
+    /// CHECK: v0 fee vault at the first release's seeds, which no longer
+    /// decodes as `FeeVault`. The size and discriminator are checked in
+    /// `FeeVaultV0::from_data`. It keeps its address once migrated.
+    #[account(
+        mut,
+        seeds = [b"fee_vault", dlmm_pool.key().as_ref()],
+        bump,
+        owner = crate::ID @ FeeRoutingError::InvalidFeeVaultVersion
+    )]
+    pub fee_vault: UncheckedAccount<'info>,
+
+    /// CHECK: DLMM pool the v0 vault was created for, bound to it by its seeds
+    pub dlmm_pool: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"program_config"],
+        bump = program_config.bump
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+
+    /// Registry page the migrated vault is listed on, created when the previous page is full
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = VaultRegistry::LEN,
+        seeds = [b"vault_registry".as_ref(), &program_config.current_registry_page().to_le_bytes()],
+        bump
+    )]
+    pub vault_registry: Account<'info, VaultRegistry>,
+
+    /// The v0 vault's authority, which keeps authority over the migrated vault
+    pub authority: Signer<'info>,
+
+    /// Pays the rent for the added bytes and any new registry page
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+use crate::{FeeVault, FeeRoutingError, ProgramConfig, VaultRegistry, FEE_VAULT_RESERVED, FEE_VAULT_VERSION};
@@ -0,0 +1,109 @@
+use anchor_lang::prelude::*;
+
+/// Most members a `VaultMultisig` can hold
+pub const MAX_MULTISIG_MEMBERS: usize = 10;
+
+/// Native M-of-N approval for vault keys.
+///
+/// A key that holds a vault role (the authority included) can be a
+/// `VaultMultisig` PDA instead of a wallet. A PDA can't sign, so instructions
+/// that check the role take it as an unsigned account and look for at least
+/// `threshold` of its members among the signers in `remaining_accounts`.
+pub struct MultisigApproval;
+
+impl MultisigApproval {
+    /// Check that `authority` approved the instruction, either by signing or,
+    /// for a multisig of `fee_vault`, through enough signing members
+    pub fn authorize(
+        authority: &AccountInfo,
+        fee_vault: &Pubkey,
+        remaining_accounts: &[AccountInfo],
+    ) -> Result<()> {
+        if authority.is_signer {
+            return Ok(());
+        }
+
+        require_keys_eq!(*authority.owner, crate::ID, FeeRoutingError::Unauthorized);
+        let multisig = VaultMultisig::try_deserialize(&mut &authority.try_borrow_data()?[..])
+            .map_err(|_| error!(FeeRoutingError::Unauthorized))?;
+        require_keys_eq!(multisig.fee_vault, *fee_vault, FeeRoutingError::Unauthorized);
+
+        let approvals = multisig.members
+            .iter()
+            .filter(|member| {
+                remaining_accounts
+                    .iter()
+                    .any(|account| account.is_signer && account.key == *member)
+            })
+            .count();
+        require!(
+            approvals >= multisig.threshold as usize,
+            FeeRoutingError::MultisigThresholdNotMet
+        );
+
+        Ok(())
+    }
+
+    pub fn validate(members: &[Pubkey], threshold: u8) -> Result<()> {
+        require!(
+            !members.is_empty() && members.len() <= MAX_MULTISIG_MEMBERS,
+            FeeRoutingError::InvalidMultisig
+        );
+        require!(
+            threshold > 0 && threshold as usize <= members.len(),
+            FeeRoutingError::InvalidMultisig
+        );
+
+        for (i, member) in members.iter().enumerate() {
+            require!(!members[..i].contains(member), FeeRoutingError::InvalidMultisig);
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct CreateVaultMultisig<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = VaultMultisig::LEN,
+        seeds = [b"vault_multisig", fee_vault.key().as_ref()],
+        bump
+    )]
+    pub vault_multisig: Account<'info, VaultMultisig>,
+
+    #[account(
+        seeds = [b"fee_vault", fee_vault.dlmm_pool.as_ref(), fee_vault.creator_seed()],
+        bump = fee_vault.bump,
+        has_one = authority
+    )]
+    pub fee_vault: Account<'info, FeeVault>,
+
+    /// CHECK: Vault authority, approved through `MultisigApproval::authorize`
+    pub authority: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateVaultMultisig<'info> {
+    #[account(
+        mut,
+        seeds = [b"vault_multisig", fee_vault.key().as_ref()],
+        bump = vault_multisig.bump,
+        has_one = fee_vault
+    )]
+    pub vault_multisig: Account<'info, VaultMultisig>,
+
+    #[account(
+        seeds = [b"fee_vault", fee_vault.dlmm_pool.as_ref(), fee_vault.creator_seed()],
+        bump = fee_vault.bump
+    )]
+    pub fee_vault: Account<'info, FeeVault>,
+}
+
+use crate::{FeeVault, FeeRoutingError, VaultMultisig};
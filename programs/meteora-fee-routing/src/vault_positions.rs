@@ -0,0 +1,247 @@
+use anchor_lang::prelude::*;
+use crate::dlmm_integration::cpi_instructions;
+use crate::dlmm_integration::meteora_dlmm_types::*;
+
+/// Bookkeeping of the DLMM positions a fee vault owns. Positions the vault
+/// opens are registered as they are created. Only registered positions can be
+/// collected from, and `close_fee_vault` refuses while any are still registered.
+pub struct VaultPositions;
+
+impl VaultPositions {
+    /// Create a DLMM position owned by the vault, with the vault PDA signing
+    /// as its owner, and register it in the same instruction
+    pub fn open(accounts: &mut OpenVaultPosition, bump: u8, lower_bin_id: i32, width: i32) -> Result<()> {
+        let seeds = accounts.fee_vault.signer_seeds();
+        let signer = &[&seeds[..]];
+
+        cpi_instructions::initialize_position(
+            CpiContext::new_with_signer(
+                accounts.dlmm_program.to_account_info(),
+                cpi_instructions::InitializePosition {
+                    payer: accounts.payer.to_account_info(),
+                    position: accounts.position.to_account_info(),
+                    lb_pair: accounts.lb_pair.to_account_info(),
+                    owner: accounts.fee_vault.to_account_info(),
+                    system_program: accounts.system_program.clone(),
+                    rent: accounts.rent.clone(),
+                    event_authority: accounts.event_authority.to_account_info(),
+                },
+                signer,
+            ),
+            lower_bin_id,
+            width,
+        )?;
+
+        Self::record(
+            &mut accounts.vault_position,
+            &mut accounts.fee_vault,
+            accounts.position.key(),
+            accounts.dlmm_program.key(),
+            accounts.payer.key(),
+            bump,
+        )
+    }
+
+    /// Record a position the vault already owns and count it on the vault
+    pub fn register(accounts: &mut RegisterVaultPosition, bump: u8) -> Result<()> {
+        Self::record(
+            &mut accounts.vault_position,
+            &mut accounts.fee_vault,
+            accounts.position.key(),
+            accounts.dlmm_program.key(),
+            accounts.payer.key(),
+            bump,
+        )
+    }
+
+    fn record<'info>(
+        vault_position: &mut Account<'info, VaultPosition>,
+        fee_vault: &mut Account<'info, FeeVault>,
+        position: Pubkey,
+        dlmm_program: Pubkey,
+        payer: Pubkey,
+        bump: u8,
+    ) -> Result<()> {
+        vault_position.fee_vault = fee_vault.key();
+        vault_position.position = position;
+        vault_position.dlmm_program = dlmm_program;
+        vault_position.payer = payer;
+        vault_position.bump = bump;
+
+        fee_vault.position_count = fee_vault.position_count
+            .checked_add(1)
+            .ok_or(FeeRoutingError::MathOverflow)?;
+        Ok(())
+    }
+
+    /// Drop a registration once DLMM has closed the position, or once it holds
+    /// no liquidity in any bin. The `VaultPosition` itself is closed by the
+    /// accounts struct.
+    pub fn deregister(accounts: &mut DeregisterVaultPosition) -> Result<()> {
+        let position = &accounts.position;
+        if !position.data_is_empty() {
+            require_keys_eq!(
+                *position.owner,
+                accounts.vault_position.dlmm_program,
+                FeeRoutingError::InvalidDlmmPosition
+            );
+            let data = position.try_borrow_data()?;
+            let position = DlmmPosition::from_bytes(&data)
+                .map_err(|_| error!(FeeRoutingError::InvalidDlmmPosition))?;
+            require!(!position.has_liquidity(), FeeRoutingError::PositionHasLiquidity);
+        }
+
+        let fee_vault = &mut accounts.fee_vault;
+        fee_vault.position_count = fee_vault.position_count
+            .checked_sub(1)
+            .ok_or(FeeRoutingError::MathOverflow)?;
+        Ok(())
+    }
+
+    /// Check a remaining-accounts `VaultPosition` registers `position` with `fee_vault`
+    pub fn require_registered(vault_position: &AccountInfo, fee_vault: &Pubkey, position: &Pubkey) -> Result<()> {
+        require_keys_eq!(*vault_position.owner, crate::ID, FeeRoutingError::PositionNotRegistered);
+        let vault_position = VaultPosition::try_deserialize(&mut &vault_position.try_borrow_data()?[..])
+            .map_err(|_| error!(FeeRoutingError::PositionNotRegistered))?;
+        require!(
+            vault_position.fee_vault == *fee_vault && vault_position.position == *position,
+            FeeRoutingError::PositionNotRegistered
+        );
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct OpenVaultPosition<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = VaultPosition::LEN,
+        seeds = [b"vault_position", fee_vault.key().as_ref(), position.key().as_ref()],
+        bump
+    )]
+    pub vault_position: Account<'info, VaultPosition>,
+
+    #[account(
+        mut,
+        seeds = [b"fee_vault", fee_vault.dlmm_pool.as_ref(), fee_vault.creator_seed()],
+        bump = fee_vault.bump,
+        has_one = authority
+    )]
+    pub fee_vault: Account<'info, FeeVault>,
+
+    /// CHECK: Vault authority, approved through `MultisigApproval::authorize`
+    pub authority: UncheckedAccount<'info>,
+
+    /// CHECK: The vault's DLMM pool, validated further by the DLMM program
+    #[account(
+        address = fee_vault.dlmm_pool @ FeeRoutingError::InvalidDlmmPool,
+        owner = dlmm_program.key() @ FeeRoutingError::InvalidDlmmPool
+    )]
+    pub lb_pair: UncheckedAccount<'info>,
+
+    /// New position keypair, created by DLMM
+    #[account(mut)]
+    pub position: Signer<'info>,
+
+    /// CHECK: `#[event_cpi]` authority PDA of `dlmm_program`
+    #[account(
+        seeds = [DLMM_EVENT_AUTHORITY_SEED],
+        bump,
+        seeds::program = dlmm_program.key()
+    )]
+    pub event_authority: UncheckedAccount<'info>,
+
+    /// CHECK: DLMM program the CPI goes to, checked against the program config allowlist
+    pub dlmm_program: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        constraint = program_config.allows_dlmm_program(&dlmm_program.key()) @ FeeRoutingError::DlmmProgramNotAllowed
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+
+    /// Pays the rent of the position and its registration
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct RegisterVaultPosition<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = VaultPosition::LEN,
+        seeds = [b"vault_position", fee_vault.key().as_ref(), position.key().as_ref()],
+        bump
+    )]
+    pub vault_position: Account<'info, VaultPosition>,
+
+    #[account(
+        mut,
+        seeds = [b"fee_vault", fee_vault.dlmm_pool.as_ref(), fee_vault.creator_seed()],
+        bump = fee_vault.bump
+    )]
+    pub fee_vault: Account<'info, FeeVault>,
+
+    /// CHECK: DLMM `Position` or `PositionV2` of the vault's pool, owned by the vault
+    #[account(
+        owner = dlmm_program.key() @ FeeRoutingError::InvalidDlmmPosition,
+        constraint = DlmmPosition::from_bytes(&position.try_borrow_data()?)?.lb_pair() == fee_vault.dlmm_pool @ FeeRoutingError::InvalidDlmmPosition,
+        constraint = DlmmPosition::from_bytes(&position.try_borrow_data()?)?.owner() == fee_vault.key() @ FeeRoutingError::InvalidPositionOwner
+    )]
+    pub position: UncheckedAccount<'info>,
+
+    /// CHECK: DLMM program owning the position, checked against the program config allowlist
+    pub dlmm_program: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        constraint = program_config.allows_dlmm_program(&dlmm_program.key()) @ FeeRoutingError::DlmmProgramNotAllowed
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct DeregisterVaultPosition<'info> {
+    #[account(
+        mut,
+        seeds = [b"vault_position", fee_vault.key().as_ref(), vault_position.position.as_ref()],
+        bump = vault_position.bump,
+        has_one = fee_vault,
+        close = rent_receiver
+    )]
+    pub vault_position: Account<'info, VaultPosition>,
+
+    #[account(
+        mut,
+        seeds = [b"fee_vault", fee_vault.dlmm_pool.as_ref(), fee_vault.creator_seed()],
+        bump = fee_vault.bump,
+        has_one = authority
+    )]
+    pub fee_vault: Account<'info, FeeVault>,
+
+    /// CHECK: Vault authority, approved through `MultisigApproval::authorize`
+    pub authority: UncheckedAccount<'info>,
+
+    /// CHECK: The registered position, read only if DLMM has not closed it yet
+    #[account(address = vault_position.position @ FeeRoutingError::InvalidDlmmPosition)]
+    pub position: UncheckedAccount<'info>,
+
+    /// CHECK: Receives the registration's rent back, pinned to whoever paid it
+    #[account(mut, address = vault_position.payer @ FeeRoutingError::Unauthorized)]
+    pub rent_receiver: UncheckedAccount<'info>,
+}
+
+use crate::{FeeVault, FeeRoutingError, ProgramConfig, VaultPosition};
@@ -0,0 +1,378 @@
+//! Golden-byte checks for the zero-copy DLMM views against the synthesized
+//! account dumps in `tests/fixtures` (regenerate with
+//! `scripts/generate-dlmm-fixtures.py`), and against any live dumps in
+//! `tests/fixtures/mainnet` (fetch with `scripts/fetch-dlmm-dumps.sh`).
+
+use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
+use base64::Engine;
+use meteora_fee_routing::dlmm_integration::meteora_dlmm_types::*;
+use std::str::FromStr;
+
+struct Fixture {
+    pubkey: Pubkey,
+    owner: Pubkey,
+    data: Vec<u8>,
+}
+
+fn load_fixture(name: &str) -> Fixture {
+    let path = format!("{}/../../tests/fixtures/{}", env!("CARGO_MANIFEST_DIR"), name);
+    let json: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    let account = &json["account"];
+
+    let data = base64::engine::general_purpose::STANDARD
+        .decode(account["data"][0].as_str().unwrap())
+        .unwrap();
+    assert_eq!(data.len() as u64, account["space"].as_u64().unwrap());
+
+    Fixture {
+        pubkey: Pubkey::from_str(json["pubkey"].as_str().unwrap()).unwrap(),
+        owner: Pubkey::from_str(account["owner"].as_str().unwrap()).unwrap(),
+        data,
+    }
+}
+
+fn key(value: &str) -> Pubkey {
+    Pubkey::from_str(value).unwrap()
+}
+
+#[test]
+fn view_sizes_match_dlmm_account_sizes() {
+    assert_eq!(8 + std::mem::size_of::<LbPair>(), 904);
+    assert_eq!(8 + std::mem::size_of::<Position>(), 7560);
+    assert_eq!(8 + std::mem::size_of::<PositionV2>(), 8120);
+    assert_eq!(std::mem::size_of::<PositionBinData>(), 112);
+    assert_eq!(std::mem::align_of::<LbPair>(), 8);
+    assert_eq!(std::mem::align_of::<Position>(), 8);
+    assert_eq!(std::mem::align_of::<PositionV2>(), 8);
+}
+
+#[test]
+fn decodes_lb_pair_fixture() {
+    let fixture = load_fixture("lb_pair.json");
+    assert_eq!(fixture.owner, <LbPair as Owner>::owner());
+    assert_eq!(fixture.owner, DLMM_PROGRAM_ID);
+
+    let lb_pair = load_from_bytes::<LbPair>(&fixture.data).unwrap();
+
+    assert_eq!(lb_pair.parameters.base_factor, 10000);
+    assert_eq!(lb_pair.parameters.max_volatility_accumulator, 150000);
+    assert_eq!(lb_pair.parameters.min_bin_id, -443636);
+    assert_eq!(lb_pair.parameters.max_bin_id, 443636);
+    assert_eq!(lb_pair.parameters.protocol_share, 500);
+    assert_eq!(lb_pair.v_parameters.id_reference, -4321);
+    assert_eq!(lb_pair.v_parameters.time_of_last_update, 1_700_000_000);
+    assert_eq!(lb_pair.bump_seed, [254]);
+    assert_eq!(lb_pair.active_id, -4321);
+    assert_eq!(lb_pair.bin_step, 10);
+    assert_eq!(lb_pair.activation_type, 1);
+    assert_eq!(lb_pair.token_x_mint, key("So11111111111111111111111111111111111111112"));
+    assert_eq!(lb_pair.token_y_mint, key("EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v"));
+    assert_eq!(lb_pair.reserve_x, key("8WZffAfkEwq5Cb83TeJMvaf6UxdR6Sp43HreUQHZLQxm"));
+    assert_eq!(lb_pair.reserve_y, key("EeVTaUpsyTbZpBCk7zYk99hQJ6Co25ZzRRE59g85zrbw"));
+    assert_eq!(lb_pair.protocol_fee.amount_x, 123_456_789);
+    assert_eq!(lb_pair.protocol_fee.amount_y, 987_654_321);
+    assert_eq!(lb_pair.padding1, [0; 32]);
+    assert_eq!(lb_pair.reward_infos[0].mint, key("21kPS2Xd5BGjUAWwkpPr6jphLSNV9b2ib8Qqrpc2pFV5"));
+    assert_eq!(lb_pair.reward_infos[0].vault, key("DGjca7TbNWYBP6pQgdoMaNfsaf4zNQ2BD3s55eAWhyZF"));
+    assert_eq!(lb_pair.reward_infos[0].reward_duration_end, 1_700_086_400);
+    assert_eq!(lb_pair.reward_infos[0].reward_rate.get(), (5u128 << 64) | 42);
+    assert_eq!(lb_pair.reward_infos[0].cumulative_seconds_with_empty_liquidity_reward, 7);
+    assert_eq!(lb_pair.reward_infos[1].mint, Pubkey::default());
+    assert_eq!(lb_pair.oracle, key("3K7G4NBR49SyDNozK13sGLfXfaHqnVnpZQqwYgaVLdGo"));
+    assert_eq!(lb_pair.bin_array_bitmap[7], 0x8000_0000_0000_0001);
+    assert_eq!(lb_pair.bin_array_bitmap[15], 2);
+    assert_eq!(lb_pair.last_updated_at, 1_700_000_200);
+    assert_eq!(lb_pair.base_key, key("6ATr64XVCt2v84FSSpsgfyeeLUyFqktJutBXvTVrJP6R"));
+    assert_eq!(lb_pair.activation_point, 123_456);
+    assert_eq!(lb_pair.pre_activation_duration, 3_600);
+    assert_eq!(lb_pair.creator, key("8jTAhy7aYW6CyvkTCEB2GJEL8rujejPzYpRots5V4AYq"));
+    assert_eq!(lb_pair.token_mint_x_program_flag, 0);
+    assert_eq!(lb_pair.token_mint_y_program_flag, 1);
+}
+
+#[test]
+fn decodes_position_fixture() {
+    let fixture = load_fixture("position.json");
+    let lb_pair = load_fixture("lb_pair.json");
+    assert_eq!(fixture.owner, <Position as Owner>::owner());
+
+    let position = load_from_bytes::<Position>(&fixture.data).unwrap();
+
+    assert_eq!(position.lb_pair, lb_pair.pubkey);
+    assert_eq!(position.owner, key("4WsLzrK5mJgG1RZdPipXTBpkuBDNjHEXqcHDLp54ibiz"));
+    assert_eq!(position.liquidity_shares[0], 1000);
+    assert_eq!(position.liquidity_shares[69], 1069);
+    assert_eq!(position.reward_infos[0].reward_per_token_completes[0].get(), (3u128 << 64) | 1);
+    assert_eq!(position.reward_infos[0].reward_per_token_completes[1].get(), 2);
+    assert_eq!(position.reward_infos[0].reward_pendings, [11, 12]);
+    assert_eq!(position.fee_infos[0].fee_x_per_token_complete.get(), (7u128 << 64) | 9);
+    assert_eq!(position.fee_infos[0].fee_y_per_token_complete.get(), 10);
+    assert_eq!(position.fee_infos[0].fee_x_pending, 500);
+    assert_eq!(position.fee_infos[0].fee_y_pending, 600);
+    assert_eq!(position.fee_infos[69].fee_x_pending, 77);
+    assert_eq!(position.lower_bin_id, -4355);
+    assert_eq!(position.upper_bin_id, -4286);
+    assert_eq!(position.last_updated_at, 1_700_000_300);
+    assert_eq!(position.total_claimed_fee_x_amount, 1111);
+    assert_eq!(position.total_claimed_fee_y_amount, 2222);
+    assert_eq!(position.total_claimed_rewards, [33, 44]);
+}
+
+#[test]
+fn decodes_extended_position_v2_fixture() {
+    let fixture = load_fixture("position_v2.json");
+    let lb_pair = load_fixture("lb_pair.json");
+    assert_eq!(fixture.owner, <PositionV2 as Owner>::owner());
+
+    let position = load_from_bytes::<PositionV2>(&fixture.data).unwrap();
+
+    assert_eq!(position.lb_pair, lb_pair.pubkey);
+    assert_eq!(position.liquidity_shares[0].get(), 1 << 64);
+    assert_eq!(position.liquidity_shares[69].get(), (1 << 64) + 69);
+    assert_eq!(position.fee_infos[0].fee_x_per_token_complete.get(), (7u128 << 64) | 9);
+    assert_eq!(position.fee_infos[0].fee_x_pending, 500);
+    assert_eq!(position.fee_infos[69].fee_x_pending, 77);
+    assert_eq!(position.lower_bin_id, -4355);
+    assert_eq!(position.upper_bin_id, -4276);
+    assert_eq!(position.total_claimed_rewards, [33, 44]);
+    assert_eq!(position.operator, key("GhjqfB2m7fx8UCKHMR1zVg5qKtW2Fmo84D9WkfF58DgB"));
+    assert_eq!(position.lock_release_point, 1_800_000_000);
+    assert_eq!(position.fee_owner, key("9iqvYPwkD4oVcpFebctDKN3Usbcs4ytNhoNvV3ivYvba"));
+
+    match DlmmPosition::from_bytes(&fixture.data).unwrap() {
+        DlmmPosition::V2 { extension, .. } => {
+            assert_eq!(extension.len(), 10);
+            assert_eq!(extension[0].liquidity_share.get(), 2000);
+            assert_eq!(extension[0].reward_info.reward_pendings, [70, 0]);
+            assert_eq!(extension[9].fee_info.fee_x_pending, 109);
+            assert_eq!(extension[9].fee_info.fee_y_pending, 209);
+        }
+        DlmmPosition::V1(_) => panic!("decoded PositionV2 as Position"),
+    }
+}
+
+#[test]
+fn decodes_either_position_variant_by_discriminator() {
+    let lb_pair = load_fixture("lb_pair.json");
+
+    for name in ["position.json", "position_v2.json"] {
+        let fixture = load_fixture(name);
+        let position = DlmmPosition::from_bytes(&fixture.data).unwrap();
+
+        assert_eq!(position.lb_pair(), lb_pair.pubkey);
+        assert_eq!(position.owner(), key("4WsLzrK5mJgG1RZdPipXTBpkuBDNjHEXqcHDLp54ibiz"));
+        assert_eq!(position.lower_bin_id(), -4355);
+        // Bins -4355..=-4286 straddle the arrays starting at -4410 and -4340
+        assert_eq!(position.bin_array_indexes(), -63..=-62);
+        assert_eq!(
+            position.fee_infos().count(),
+            position_width(position.lower_bin_id(), position.upper_bin_id()).unwrap()
+        );
+    }
+
+    assert!(DlmmPosition::from_bytes(&lb_pair.data).is_err());
+}
+
+#[test]
+fn rejects_position_v2_missing_extension_bins() {
+    let fixture = load_fixture("position_v2.json");
+
+    assert!(DlmmPosition::from_bytes(&fixture.data[..fixture.data.len() - 112]).is_err());
+}
+
+#[test]
+fn detects_liquidity_in_any_bin() {
+    let mut position = load_fixture("position.json").data;
+    position[72..72 + 8 * DEFAULT_BIN_PER_POSITION].fill(0);
+    assert!(!DlmmPosition::from_bytes(&position).unwrap().has_liquidity());
+    position[72 + 8 * 69] = 1;
+    assert!(DlmmPosition::from_bytes(&position).unwrap().has_liquidity());
+
+    let mut position_v2 = load_fixture("position_v2.json").data;
+    let extension = 8 + std::mem::size_of::<PositionV2>();
+    let bin_size = std::mem::size_of::<PositionBinData>();
+    position_v2[72..72 + 16 * DEFAULT_BIN_PER_POSITION].fill(0);
+    for bin in position_v2[extension..].chunks_exact_mut(bin_size) {
+        bin[..16].fill(0);
+    }
+    assert!(!DlmmPosition::from_bytes(&position_v2).unwrap().has_liquidity());
+
+    // Liquidity past the first 70 bins lives in the extension
+    position_v2[extension + bin_size] = 1;
+    assert!(DlmmPosition::from_bytes(&position_v2).unwrap().has_liquidity());
+}
+
+#[test]
+fn event_authority_matches_dlmm_pda() {
+    let (event_authority, _) = Pubkey::find_program_address(&[DLMM_EVENT_AUTHORITY_SEED], &DLMM_PROGRAM_ID);
+    assert_eq!(event_authority, DLMM_EVENT_AUTHORITY);
+}
+
+#[test]
+fn rejects_mismatched_discriminator() {
+    let lb_pair = load_fixture("lb_pair.json");
+    let position = load_fixture("position.json");
+
+    assert!(load_from_bytes::<Position>(&lb_pair.data).is_err());
+    assert!(load_from_bytes::<LbPair>(&position.data).is_err());
+    assert_eq!(&lb_pair.data[..8], LbPair::DISCRIMINATOR);
+    assert_eq!(&position.data[..8], Position::DISCRIMINATOR);
+}
+
+#[test]
+fn loads_accounts_owned_by_any_dlmm_deployment() {
+    let mut fixture = load_fixture("lb_pair.json");
+    let owner = Pubkey::new_unique();
+    let mut lamports = 0;
+    let account = AccountInfo::new(
+        &fixture.pubkey,
+        false,
+        true,
+        &mut lamports,
+        &mut fixture.data,
+        &owner,
+        false,
+        0,
+    );
+
+    let lb_pair = load_account::<LbPair>(&account).unwrap();
+    assert_eq!(lb_pair.bin_step, 10);
+    drop(lb_pair);
+
+    account.try_borrow_mut_data().unwrap()[0] ^= 1;
+    assert!(load_account::<LbPair>(&account).is_err());
+}
+
+#[test]
+fn rejects_truncated_data() {
+    let fixture = load_fixture("lb_pair.json");
+
+    assert!(load_from_bytes::<LbPair>(&fixture.data[..fixture.data.len() - 1]).is_err());
+    assert!(load_from_bytes::<LbPair>(&fixture.data[..4]).is_err());
+}
+
+#[test]
+fn collect_pool_fixtures_sit_at_dlmm_pdas() {
+    let fixture = load_fixture("collect_lb_pair.json");
+    assert_eq!(fixture.owner, DLMM_PROGRAM_ID);
+    let lb_pair = load_from_bytes::<LbPair>(&fixture.data).unwrap();
+
+    // DLMM signs for the reserves with the permissionless pair seeds
+    let (min_mint, max_mint) = if lb_pair.token_x_mint < lb_pair.token_y_mint {
+        (lb_pair.token_x_mint, lb_pair.token_y_mint)
+    } else {
+        (lb_pair.token_y_mint, lb_pair.token_x_mint)
+    };
+    let pair_address = Pubkey::create_program_address(
+        &[min_mint.as_ref(), max_mint.as_ref(), &lb_pair.bin_step.to_le_bytes(), &lb_pair.bump_seed],
+        &DLMM_PROGRAM_ID,
+    )
+    .unwrap();
+    assert_eq!(fixture.pubkey, pair_address);
+
+    let pda = |seeds: &[&[u8]]| Pubkey::find_program_address(seeds, &DLMM_PROGRAM_ID).0;
+    for (name, address, mint) in [
+        ("collect_reserve_x.json", lb_pair.reserve_x, lb_pair.token_x_mint),
+        ("collect_reserve_y.json", lb_pair.reserve_y, lb_pair.token_y_mint),
+        ("collect_reward_vault.json", lb_pair.reward_infos[0].vault, lb_pair.reward_infos[0].mint),
+    ] {
+        let reserve = load_fixture(name);
+        assert_eq!(reserve.pubkey, address);
+        assert_eq!(reserve.data[..32], mint.to_bytes());
+        assert_eq!(reserve.data[32..64], fixture.pubkey.to_bytes());
+    }
+    assert_eq!(lb_pair.reserve_x, pda(&[fixture.pubkey.as_ref(), lb_pair.token_x_mint.as_ref()]));
+    assert_eq!(lb_pair.reward_infos[0].vault, pda(&[fixture.pubkey.as_ref(), &0u64.to_le_bytes()]));
+    assert_eq!(
+        load_fixture("collect_bin_array.json").pubkey,
+        pda(&[b"bin_array", fixture.pubkey.as_ref(), &0i64.to_le_bytes()])
+    );
+
+    // Every position belongs to the vault the pool creator opens, which is also a claim fee operator
+    let (fee_vault, _) = meteora_fee_routing::FeeVault::find_address(&fixture.pubkey, &lb_pair.creator);
+    let operator = load_fixture("collect_claim_fee_operator.json");
+    assert_eq!(operator.pubkey, pda(&[CLAIM_FEE_OPERATOR_SEED, fee_vault.as_ref()]));
+    assert_eq!(load_from_bytes::<ClaimFeeOperator>(&operator.data).unwrap().operator, fee_vault);
+
+    let mut pending = Vec::new();
+    for index in 0..3 {
+        let position = load_fixture(&format!("collect_position_{index}.json"));
+        let position = DlmmPosition::from_bytes(&position.data).unwrap();
+        assert_eq!(position.lb_pair(), fixture.pubkey);
+        assert_eq!(position.owner(), fee_vault);
+        assert_eq!(position.bin_array_indexes(), 0..=0);
+        assert!(!position.has_liquidity());
+        pending.push(position.fee_infos().fold((0, 0), |(x, y), fee| {
+            (x + fee.fee_x_pending, y + fee.fee_y_pending)
+        }));
+    }
+    assert_eq!(pending, [(150, 1_500), (200, 2_000), (400, 4_000)]);
+}
+
+/// Live accounts dumped by `scripts/fetch-dlmm-dumps.sh`, listed with their
+/// cluster and slot in `tests/fixtures/mainnet/ADDRESSES`
+fn mainnet_dumps() -> Vec<Fixture> {
+    let dir = format!("{}/../../tests/fixtures/mainnet", env!("CARGO_MANIFEST_DIR"));
+    let entries = std::fs::read_dir(&dir)
+        .unwrap_or_else(|_| panic!("{dir} is missing, fetch it with scripts/fetch-dlmm-dumps.sh"));
+    let addresses = std::fs::read_to_string(format!("{dir}/ADDRESSES")).unwrap();
+
+    let mut names: Vec<String> = entries
+        .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+        .filter(|name| name.ends_with(".json"))
+        .collect();
+    names.sort();
+    assert!(!names.is_empty(), "{dir} holds no dumps");
+
+    let dumps: Vec<Fixture> = names.iter().map(|name| load_fixture(&format!("mainnet/{name}"))).collect();
+    for dump in &dumps {
+        let source = addresses
+            .lines()
+            .find(|line| line.starts_with(&format!("{} ", dump.pubkey)))
+            .unwrap_or_else(|| panic!("{} is not listed in ADDRESSES", dump.pubkey));
+        assert!(source.contains(" slot "), "{source} does not record its slot");
+    }
+    dumps
+}
+
+#[test]
+fn decodes_mainnet_dumps() {
+    let dumps = mainnet_dumps();
+    let mut pairs = Vec::new();
+
+    for dump in dumps.iter().filter(|dump| dump.data[..8] == *LbPair::DISCRIMINATOR) {
+        assert_eq!(dump.owner, DLMM_PROGRAM_ID);
+        assert_eq!(dump.data.len(), 904);
+        let lb_pair = load_from_bytes::<LbPair>(&dump.data).unwrap();
+
+        assert_eq!(lb_pair.bin_step, u16::from_le_bytes(lb_pair.bin_step_seed));
+        assert!(lb_pair.parameters.min_bin_id <= lb_pair.active_id);
+        assert!(lb_pair.active_id <= lb_pair.parameters.max_bin_id);
+        assert_ne!(lb_pair.token_x_mint, lb_pair.token_y_mint);
+        assert_ne!(lb_pair.reserve_x, Pubkey::default());
+        assert_ne!(lb_pair.reserve_y, Pubkey::default());
+        assert!(lb_pair.token_mint_x_program_flag <= 1 && lb_pair.token_mint_y_program_flag <= 1);
+        pairs.push(dump.pubkey);
+    }
+
+    assert!(!pairs.is_empty(), "no LbPair dump");
+
+    let mut variants = Vec::new();
+    for dump in dumps.iter().filter(|dump| dump.data[..8] != *LbPair::DISCRIMINATOR) {
+        assert_eq!(dump.owner, DLMM_PROGRAM_ID);
+        let position = DlmmPosition::from_bytes(&dump.data).unwrap();
+        variants.push(dump.data[..8] == *PositionV2::DISCRIMINATOR);
+
+        assert!(pairs.contains(&position.lb_pair()));
+        assert_ne!(position.owner(), Pubkey::default());
+        assert_eq!(
+            position.fee_infos().count(),
+            position_width(position.lower_bin_id(), position.upper_bin_id()).unwrap()
+        );
+    }
+
+    assert!(variants.contains(&false), "no Position dump");
+    assert!(variants.contains(&true), "no PositionV2 dump");
+}
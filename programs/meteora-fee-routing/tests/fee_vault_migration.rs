@@ -0,0 +1,155 @@
+//! Decodes the raw v0 fee vault in `tests/fixtures/fee_vault_v0.json`
+//! (regenerate with `scripts/generate-dlmm-fixtures.py`), as the first release
+//! wrote it, and converts it to the current layout at the same address.
+
+use anchor_lang::prelude::*;
+use anchor_spl::associated_token::get_associated_token_address;
+use base64::Engine;
+use meteora_fee_routing::dlmm_integration::meteora_dlmm_types::{DlmmPosition, DLMM_PROGRAM_ID};
+use meteora_fee_routing::{FeeVault, FeeVaultV0, FEE_VAULT_RESERVED, FEE_VAULT_VERSION};
+use std::str::FromStr;
+
+/// Address, owner and data of a fixture account
+fn load_fixture(name: &str) -> (Pubkey, Pubkey, Vec<u8>) {
+    let path = format!("{}/../../tests/fixtures/{name}", env!("CARGO_MANIFEST_DIR"));
+    let json: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    let account = &json["account"];
+
+    (
+        Pubkey::from_str(json["pubkey"].as_str().unwrap()).unwrap(),
+        Pubkey::from_str(account["owner"].as_str().unwrap()).unwrap(),
+        base64::engine::general_purpose::STANDARD
+            .decode(account["data"][0].as_str().unwrap())
+            .unwrap(),
+    )
+}
+
+fn load_v0_fixture() -> (Pubkey, Vec<u8>) {
+    let (address, owner, data) = load_fixture("fee_vault_v0.json");
+    assert_eq!(owner, meteora_fee_routing::ID);
+    (address, data)
+}
+
+#[test]
+fn v0_fixture_has_the_first_release_layout() {
+    let (address, data) = load_v0_fixture();
+    assert_eq!(data.len(), 161);
+    assert_eq!(FeeVaultV0::LEN, 161);
+
+    // The first release keyed vaults by pool alone
+    let fee_vault = FeeVaultV0::from_data(&data).unwrap();
+    let (expected, bump) = Pubkey::find_program_address(
+        &[b"fee_vault", fee_vault.dlmm_pool.as_ref()],
+        &meteora_fee_routing::ID,
+    );
+    assert_eq!(address, expected);
+    assert_eq!(fee_vault.bump, bump);
+}
+
+#[test]
+fn v0_fixture_token_accounts_are_the_vault_atas() {
+    let (address, data) = load_v0_fixture();
+    let fee_vault = FeeVaultV0::from_data(&data).unwrap();
+
+    for (name, mint, amount) in [
+        ("fee_vault_v0_base.json", fee_vault.base_mint, 400),
+        ("fee_vault_v0_quote.json", fee_vault.quote_mint, 500),
+    ] {
+        let (token_account, owner, data) = load_fixture(name);
+        assert_eq!(owner, anchor_spl::token::ID);
+        assert_eq!(token_account, get_associated_token_address(&address, &mint));
+        assert_eq!(data[..32], mint.to_bytes());
+        assert_eq!(data[32..64], address.to_bytes());
+        assert_eq!(u64::from_le_bytes(data[64..72].try_into().unwrap()), amount);
+    }
+}
+
+#[test]
+fn converts_v0_fields_and_fills_defaults() {
+    let (_, data) = load_v0_fixture();
+    let legacy = FeeVaultV0::from_data(&data).unwrap();
+    let creator = Pubkey::new_unique();
+    let (_, bump) = FeeVault::find_address(&legacy.dlmm_pool, &creator);
+
+    let fee_vault = legacy.clone().into_current(creator, bump, 3_600);
+
+    assert_eq!(fee_vault.authority, legacy.authority);
+    assert_eq!(fee_vault.dlmm_pool, legacy.dlmm_pool);
+    assert_eq!(fee_vault.base_mint, legacy.base_mint);
+    assert_eq!(fee_vault.quote_mint, legacy.quote_mint);
+    assert_eq!(fee_vault.creator, creator);
+    assert_eq!(fee_vault.bump, bump);
+    assert_eq!(fee_vault.total_collected_base, 5_000);
+    assert_eq!(fee_vault.total_collected_quote, 6_000);
+    assert_eq!(fee_vault.total_collected_gross_quote, 6_000);
+    assert_eq!(fee_vault.creation_time, 1_700_000_000);
+    assert_eq!(fee_vault.base_token_program, anchor_spl::token::ID);
+    assert_eq!(fee_vault.quote_token_program, anchor_spl::token::ID);
+    assert_eq!(fee_vault.config_delay, 3_600);
+    assert_eq!(fee_vault.total_shares, 0);
+    assert_eq!(fee_vault.pending_authority, None);
+    assert_eq!(fee_vault.position_count, 0);
+    assert_eq!(fee_vault.version, FEE_VAULT_VERSION);
+    assert_eq!(fee_vault.reserved, [0; FEE_VAULT_RESERVED]);
+
+    // Fits the current account size and reads back
+    let mut data = vec![0; FeeVault::LEN];
+    fee_vault.try_serialize(&mut &mut data[..]).unwrap();
+    let decoded = FeeVault::try_deserialize(&mut &data[..]).unwrap();
+    assert_eq!(decoded.creator, creator);
+    assert_eq!(decoded.total_collected_base, 5_000);
+
+    // Already in the current layout, so there is nothing to migrate
+    assert!(FeeVaultV0::from_data(&data).is_err());
+}
+
+#[test]
+fn default_creator_signs_with_the_legacy_seeds() {
+    let (address, data) = load_v0_fixture();
+    let legacy = FeeVaultV0::from_data(&data).unwrap();
+    let fee_vault = legacy.clone().into_current(Pubkey::default(), legacy.bump, 0);
+
+    assert!(fee_vault.creator_seed().is_empty());
+    assert_eq!(
+        Pubkey::create_program_address(&fee_vault.signer_seeds(), &meteora_fee_routing::ID).unwrap(),
+        address
+    );
+
+    // Vaults keyed by a creator still sign with it
+    let creator = Pubkey::new_unique();
+    let (keyed, bump) = FeeVault::find_address(&legacy.dlmm_pool, &creator);
+    let fee_vault = legacy.into_current(creator, bump, 0);
+    assert_eq!(
+        Pubkey::create_program_address(&fee_vault.signer_seeds(), &meteora_fee_routing::ID).unwrap(),
+        keyed
+    );
+}
+
+#[test]
+fn migrated_vault_signs_for_the_position_it_owns() {
+    let (address, data) = load_v0_fixture();
+    let legacy = FeeVaultV0::from_data(&data).unwrap();
+    let bump = legacy.bump;
+    let fee_vault = legacy.into_current(Pubkey::default(), bump, 0);
+    let signer = Pubkey::create_program_address(&fee_vault.signer_seeds(), &meteora_fee_routing::ID).unwrap();
+
+    let (_, owner, position) = load_fixture("fee_vault_v0_position.json");
+    assert_eq!(owner, DLMM_PROGRAM_ID);
+    let position = DlmmPosition::from_bytes(&position).unwrap();
+    assert_eq!(position.lb_pair(), fee_vault.dlmm_pool);
+    assert_eq!(position.owner(), address);
+    assert_eq!(position.owner(), signer);
+
+    let pending = position.fee_infos().nth(30).unwrap();
+    assert_eq!((pending.fee_x_pending, pending.fee_y_pending), (250, 2_500));
+}
+
+#[test]
+fn rejects_data_that_is_not_a_v0_fee_vault() {
+    let (_, mut data) = load_v0_fixture();
+    assert!(FeeVaultV0::from_data(&data[..FeeVaultV0::LEN - 1]).is_err());
+
+    data[0] ^= 1;
+    assert!(FeeVaultV0::from_data(&data).is_err());
+}
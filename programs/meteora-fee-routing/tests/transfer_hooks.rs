@@ -0,0 +1,142 @@
+//! Checks how transfer hook accounts are split into DLMM remaining account
+//! slices when collecting for hooked Token-2022 mints.
+
+use anchor_lang::prelude::*;
+use anchor_spl::token_2022::spl_token_2022::extension::transfer_hook::TransferHook;
+use anchor_spl::token_2022::spl_token_2022::extension::{
+    BaseStateWithExtensionsMut, ExtensionType, StateWithExtensionsMut,
+};
+use anchor_spl::token_2022::spl_token_2022::{self, state::Mint};
+use meteora_fee_routing::dlmm_integration::cpi_instructions::AccountsType;
+use meteora_fee_routing::TransferHooks;
+
+/// Key, owner, lamports and data backing one `AccountInfo`
+struct TestAccount {
+    key: Pubkey,
+    owner: Pubkey,
+    lamports: u64,
+    data: Vec<u8>,
+}
+
+impl TestAccount {
+    fn new(key: Pubkey, owner: Pubkey, data: Vec<u8>) -> Self {
+        Self { key, owner, lamports: 0, data }
+    }
+
+    fn info(&mut self) -> AccountInfo<'_> {
+        AccountInfo::new(&self.key, false, true, &mut self.lamports, &mut self.data, &self.owner, false, 0)
+    }
+}
+
+/// Token-2022 mint routing transfers through `hook_program`, or a plain mint
+fn mint(hook_program: Option<Pubkey>) -> TestAccount {
+    let extensions: &[ExtensionType] = if hook_program.is_some() { &[ExtensionType::TransferHook] } else { &[] };
+    let mut data = vec![0; ExtensionType::try_calculate_account_len::<Mint>(extensions).unwrap()];
+
+    let mut state = StateWithExtensionsMut::<Mint>::unpack_uninitialized(&mut data).unwrap();
+    if let Some(hook_program) = hook_program {
+        state.init_extension::<TransferHook>(true).unwrap().program_id = Some(hook_program).try_into().unwrap();
+        state.init_account_type().unwrap();
+    }
+    state.base = Mint { decimals: 6, is_initialized: true, ..Mint::default() };
+    state.pack_base();
+
+    TestAccount::new(Pubkey::new_unique(), spl_token_2022::ID, data)
+}
+
+/// `ExtraAccountMetaList` for `mint` listing `extra_accounts` entries
+fn validation_account(mint: &Pubkey, hook_program: &Pubkey, extra_accounts: u32) -> TestAccount {
+    let (key, _) = Pubkey::find_program_address(&[b"extra-account-metas", mint.as_ref()], hook_program);
+
+    let mut data = vec![105, 37, 101, 197, 75, 251, 102, 26];
+    data.extend_from_slice(&(4 + 35 * extra_accounts).to_le_bytes());
+    data.extend_from_slice(&extra_accounts.to_le_bytes());
+    data.resize(data.len() + 35 * extra_accounts as usize, 0);
+
+    TestAccount::new(key, *hook_program, data)
+}
+
+fn hook_group(mint: &TestAccount, hook_program: &Pubkey, extra_accounts: u32) -> Vec<TestAccount> {
+    let mut group: Vec<TestAccount> = (0..extra_accounts)
+        .map(|_| TestAccount::new(Pubkey::new_unique(), *hook_program, vec![]))
+        .collect();
+    group.push(TestAccount::new(*hook_program, Pubkey::default(), vec![]));
+    group.push(validation_account(&mint.key, hook_program, extra_accounts));
+    group
+}
+
+#[test]
+fn gives_each_hooked_mint_only_its_own_accounts() {
+    let hook_program = Pubkey::new_unique();
+    let mut base_mint = mint(Some(hook_program));
+    let mut quote_mint = mint(Some(hook_program));
+
+    let mut hook_accounts = hook_group(&base_mint, &hook_program, 1);
+    hook_accounts.extend(hook_group(&quote_mint, &hook_program, 2));
+    let keys: Vec<Pubkey> = hook_accounts.iter().map(|account| account.key).collect();
+    let infos: Vec<AccountInfo> = hook_accounts.iter_mut().map(TestAccount::info).collect();
+
+    // Quote first, to show slices follow the mint order rather than the passed order
+    let (info, accounts) = TransferHooks::dlmm_remaining_accounts(
+        &[
+            (AccountsType::TransferHookY, quote_mint.info()),
+            (AccountsType::TransferHookX, base_mint.info()),
+        ],
+        &infos,
+    )
+    .unwrap();
+
+    assert_eq!(info.slices.len(), 2);
+    assert_eq!(info.slices[0].accounts_type, AccountsType::TransferHookY);
+    assert_eq!(info.slices[0].length, 4);
+    assert_eq!(info.slices[1].accounts_type, AccountsType::TransferHookX);
+    assert_eq!(info.slices[1].length, 3);
+
+    let forwarded: Vec<Pubkey> = accounts.iter().map(|account| account.key()).collect();
+    assert_eq!(forwarded[..4], keys[3..]);
+    assert_eq!(forwarded[4..], keys[..3]);
+}
+
+#[test]
+fn skips_mints_without_a_hook() {
+    let hook_program = Pubkey::new_unique();
+    let mut base_mint = mint(Some(hook_program));
+    let mut quote_mint = mint(None);
+
+    let mut hook_accounts = hook_group(&base_mint, &hook_program, 1);
+    let infos: Vec<AccountInfo> = hook_accounts.iter_mut().map(TestAccount::info).collect();
+
+    let (info, accounts) = TransferHooks::dlmm_remaining_accounts(
+        &[
+            (AccountsType::TransferHookX, base_mint.info()),
+            (AccountsType::TransferHookY, quote_mint.info()),
+        ],
+        &infos,
+    )
+    .unwrap();
+
+    assert_eq!(info.slices.len(), 1);
+    assert_eq!(info.slices[0].accounts_type, AccountsType::TransferHookX);
+    assert_eq!(accounts.len(), 3);
+}
+
+#[test]
+fn rejects_missing_or_misordered_hook_accounts() {
+    let hook_program = Pubkey::new_unique();
+    let mut base_mint = mint(Some(hook_program));
+
+    // No accounts for a hooked mint
+    assert!(TransferHooks::dlmm_remaining_accounts(&[(AccountsType::TransferHookX, base_mint.info())], &[]).is_err());
+
+    // Hook program missing from in front of the validation account
+    let mut hook_accounts = hook_group(&base_mint, &hook_program, 1);
+    hook_accounts.swap(0, 1);
+    let infos: Vec<AccountInfo> = hook_accounts.iter_mut().map(TestAccount::info).collect();
+    assert!(TransferHooks::dlmm_remaining_accounts(&[(AccountsType::TransferHookX, base_mint.info())], &infos).is_err());
+
+    // Fewer extra accounts than the validation account lists
+    let mut hook_accounts = hook_group(&base_mint, &hook_program, 2);
+    hook_accounts.remove(0);
+    let infos: Vec<AccountInfo> = hook_accounts.iter_mut().map(TestAccount::info).collect();
+    assert!(TransferHooks::dlmm_remaining_accounts(&[(AccountsType::TransferHookX, base_mint.info())], &infos).is_err());
+}
@@ -0,0 +1,106 @@
+use anchor_lang::prelude::*;
+use spl_discriminator::SplDiscriminate;
+use spl_tlv_account_resolution::{account::ExtraAccountMeta, seeds::Seed, state::ExtraAccountMetaList};
+use spl_transfer_hook_interface::instruction::ExecuteInstruction;
+
+declare_id!("7sVpnWGgjWnd6twfodXqdCYEzuYhfHaB9D8AreJkgRkP");
+
+/// Minimal Token-2022 transfer hook for the fee routing tests. Every transfer of
+/// a hooked mint needs one extra account, a per-mint counter PDA resolved from
+/// the mint's `ExtraAccountMetaList`, and the hook bumps it so tests can tell
+/// the extra accounts made it through.
+#[program]
+pub mod mock_transfer_hook {
+    use super::*;
+
+    /// Write the mint's `ExtraAccountMetaList` and create its transfer counter
+    pub fn initialize_extra_account_meta_list(
+        ctx: Context<InitializeExtraAccountMetaList>,
+    ) -> Result<()> {
+        ExtraAccountMetaList::init::<ExecuteInstruction>(
+            &mut ctx.accounts.extra_account_meta_list.try_borrow_mut_data()?,
+            &extra_account_metas()?,
+        )?;
+        ctx.accounts.counter.transfers = 0;
+        Ok(())
+    }
+
+    /// Transfer hook `Execute`, called by Token-2022 on every transfer of the mint
+    #[instruction(discriminator = ExecuteInstruction::SPL_DISCRIMINATOR_SLICE)]
+    pub fn execute(ctx: Context<Execute>, _amount: u64) -> Result<()> {
+        let counter = &mut ctx.accounts.counter;
+        counter.transfers = counter.transfers.saturating_add(1);
+        Ok(())
+    }
+}
+
+/// The one extra account every transfer needs: `["counter", mint]`
+fn extra_account_metas() -> Result<Vec<ExtraAccountMeta>> {
+    Ok(vec![ExtraAccountMeta::new_with_seeds(
+        &[
+            Seed::Literal { bytes: b"counter".to_vec() },
+            Seed::AccountKey { index: 1 },
+        ],
+        false,
+        true,
+    )?])
+}
+
+#[derive(Accounts)]
+pub struct InitializeExtraAccountMetaList<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// CHECK: Validation account, written by `ExtraAccountMetaList::init`
+    #[account(
+        init,
+        payer = payer,
+        space = ExtraAccountMetaList::size_of(1)?,
+        seeds = [b"extra-account-metas", mint.key().as_ref()],
+        bump
+    )]
+    pub extra_account_meta_list: UncheckedAccount<'info>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = TransferCounter::LEN,
+        seeds = [b"counter", mint.key().as_ref()],
+        bump
+    )]
+    pub counter: Account<'info, TransferCounter>,
+
+    /// CHECK: Token-2022 mint whose transfer hook points at this program
+    pub mint: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct Execute<'info> {
+    /// CHECK: Source token account, checked by Token-2022
+    pub source: UncheckedAccount<'info>,
+    /// CHECK: Hooked mint, checked by Token-2022
+    pub mint: UncheckedAccount<'info>,
+    /// CHECK: Destination token account, checked by Token-2022
+    pub destination: UncheckedAccount<'info>,
+    /// CHECK: Source authority, checked by Token-2022
+    pub owner: UncheckedAccount<'info>,
+
+    /// CHECK: Validation account for the mint
+    #[account(seeds = [b"extra-account-metas", mint.key().as_ref()], bump)]
+    pub extra_account_meta_list: UncheckedAccount<'info>,
+
+    #[account(mut, seeds = [b"counter", mint.key().as_ref()], bump)]
+    pub counter: Account<'info, TransferCounter>,
+}
+
+#[account]
+pub struct TransferCounter {
+    pub transfers: u64,
+}
+
+impl TransferCounter {
+    pub const LEN: usize = 8 + // discriminator
+        8;   // transfers
+}
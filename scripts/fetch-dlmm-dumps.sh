@@ -0,0 +1,44 @@
+#!/bin/bash
+
+# Dump live DLMM accounts into tests/fixtures/mainnet, one
+# `solana account <address> --output json` file per account, named by address.
+# The layout tests decode every dump found there against the zero-copy views.
+#
+#   ./scripts/fetch-dlmm-dumps.sh <LB_PAIR> <POSITION>...
+#
+# Pass a pool and positions of that pool, ideally both a `Position` and a
+# `PositionV2`. Set SOLANA_URL to fetch from somewhere other than mainnet-beta.
+
+set -e
+
+if ! command -v solana &> /dev/null; then
+    echo "❌ Solana CLI not found. Please install Solana CLI first."
+    exit 1
+fi
+
+if [ "$#" -lt 2 ]; then
+    echo "Usage: $0 <LB_PAIR> <POSITION>..."
+    exit 1
+fi
+
+URL=${SOLANA_URL:-mainnet-beta}
+DLMM_PROGRAM_ID="LBUZKhRxPF3XUpBCjp4YzTKgLccjZhTSDM9YuVaPwxo"
+DUMPS_DIR="$(dirname "$0")/../tests/fixtures/mainnet"
+mkdir -p "$DUMPS_DIR"
+
+SLOT=$(solana slot --url "$URL")
+for ADDRESS in "$@"; do
+    echo "📥 $ADDRESS"
+    solana account "$ADDRESS" --output json --url "$URL" > "$DUMPS_DIR/$ADDRESS.json"
+
+    if ! grep -q "\"owner\": *\"$DLMM_PROGRAM_ID\"" "$DUMPS_DIR/$ADDRESS.json"; then
+        echo "❌ $ADDRESS is not owned by the DLMM program"
+        rm "$DUMPS_DIR/$ADDRESS.json"
+        exit 1
+    fi
+
+    # Where each dump came from, kept next to the dumps
+    echo "$ADDRESS $URL slot $SLOT" >> "$DUMPS_DIR/ADDRESSES"
+done
+
+echo "✅ Dumped $# accounts into $DUMPS_DIR"
@@ -0,0 +1,30 @@
+#!/bin/bash
+
+# Dump the deployed DLMM program into tests/fixtures/programs/lb_clmm.so, which
+# Anchor.toml loads into the test validator at genesis so `anchor test` runs
+# offline. Commit the binary along with the SOURCE file written next to it.
+#
+#   ./scripts/fetch-dlmm-program.sh
+#
+# Set SOLANA_URL to fetch from somewhere other than mainnet-beta.
+
+set -e
+
+if ! command -v solana &> /dev/null; then
+    echo "❌ Solana CLI not found. Please install Solana CLI first."
+    exit 1
+fi
+
+URL=${SOLANA_URL:-mainnet-beta}
+DLMM_PROGRAM_ID="LBUZKhRxPF3XUpBCjp4YzTKgLccjZhTSDM9YuVaPwxo"
+PROGRAMS_DIR="$(dirname "$0")/../tests/fixtures/programs"
+mkdir -p "$PROGRAMS_DIR"
+
+SLOT=$(solana slot --url "$URL")
+echo "📥 $DLMM_PROGRAM_ID"
+solana program dump "$DLMM_PROGRAM_ID" "$PROGRAMS_DIR/lb_clmm.so" --url "$URL"
+
+# Where the binary came from, and its hash so a stale or swapped binary shows up in review
+echo "$DLMM_PROGRAM_ID $URL slot $SLOT sha256 $(sha256sum "$PROGRAMS_DIR/lb_clmm.so" | cut -d' ' -f1)" > "$PROGRAMS_DIR/SOURCE"
+
+echo "✅ Dumped the DLMM program into $PROGRAMS_DIR/lb_clmm.so"
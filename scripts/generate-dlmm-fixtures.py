@@ -0,0 +1,441 @@
+#!/usr/bin/env python3
+"""Writes the DLMM account fixtures in tests/fixtures, along with a v0 fee vault
+and its token accounts for the migration tests.
+
+The files use the same JSON shape as `solana account <address> --output json`,
+so they can be loaded by `solana-test-validator` (see Anchor.toml) and swapped
+for real account dumps. Every field is written at its byte offset in Meteora's
+zero-copy layout, independently of the Rust views, so the golden tests in
+programs/meteora-fee-routing/tests catch any drift in those views.
+"""
+
+import base64
+import hashlib
+import json
+import os
+import struct
+
+ALPHABET = "123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz"
+
+DLMM_PROGRAM_ID = "LBUZKhRxPF3XUpBCjp4YzTKgLccjZhTSDM9YuVaPwxo"
+FEE_ROUTING_PROGRAM_ID = "FeeRtG9mEpMFEBPqhN5xjLrP4KdE5FGHxFpEhGkGKQW"
+TOKEN_PROGRAM_ID = "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA"
+ASSOCIATED_TOKEN_PROGRAM_ID = "ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL"
+WSOL_MINT = "So11111111111111111111111111111111111111112"
+USDC_MINT = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v"
+
+FIXTURES_DIR = os.path.join(os.path.dirname(__file__), "..", "tests", "fixtures")
+
+
+def b58encode(raw):
+    n = int.from_bytes(raw, "big")
+    out = ""
+    while n:
+        n, rem = divmod(n, 58)
+        out = ALPHABET[rem] + out
+    pad = len(raw) - len(raw.lstrip(b"\0"))
+    return "1" * pad + out
+
+
+def b58decode(text):
+    n = 0
+    for ch in text:
+        n = n * 58 + ALPHABET.index(ch)
+    raw = n.to_bytes(32, "big")
+    return raw
+
+
+def key(label):
+    """Deterministic 32-byte key for a fixture label."""
+    return hashlib.sha256(b"meteora-fee-routing-fixture:" + label.encode()).digest()
+
+
+# Ed25519, only as far as needed to derive keypair public keys and to find
+# program addresses, which must be off the curve
+ED_P = 2**255 - 19
+ED_D = -121665 * pow(121666, ED_P - 2, ED_P) % ED_P
+ED_I = pow(2, (ED_P - 1) // 4, ED_P)
+
+
+def ed_recover_x(y, sign):
+    x2 = (y * y - 1) * pow(ED_D * y * y + 1, ED_P - 2, ED_P) % ED_P
+    if x2 == 0:
+        return None if sign else 0
+    x = pow(x2, (ED_P + 3) // 8, ED_P)
+    if (x * x - x2) % ED_P:
+        x = x * ED_I % ED_P
+    if (x * x - x2) % ED_P:
+        return None
+    return ED_P - x if x & 1 != sign else x
+
+
+ED_BASE_Y = 4 * pow(5, ED_P - 2, ED_P) % ED_P
+ED_BASE_X = ed_recover_x(ED_BASE_Y, 0)
+ED_BASE = (ED_BASE_X, ED_BASE_Y, 1, ED_BASE_X * ED_BASE_Y % ED_P)
+
+
+def ed_add(a, b):
+    x1, y1, z1, t1 = a
+    x2, y2, z2, t2 = b
+    e = (y1 - x1) * (y2 - x2) % ED_P
+    f = (y1 + x1) * (y2 + x2) % ED_P
+    g = 2 * t1 * t2 * ED_D % ED_P
+    h = 2 * z1 * z2 % ED_P
+    e, f, g, h = f - e, h - g, h + g, f + e
+    return (e * f % ED_P, g * h % ED_P, f * g % ED_P, e * h % ED_P)
+
+
+def ed_mul(scalar, point):
+    out = (0, 1, 1, 0)
+    while scalar:
+        if scalar & 1:
+            out = ed_add(out, point)
+        point = ed_add(point, point)
+        scalar >>= 1
+    return out
+
+
+def keypair_public_key(seed):
+    """Public key of the keypair `Keypair.fromSeed(seed)` creates in web3.js"""
+    digest = hashlib.sha512(seed).digest()
+    scalar = int.from_bytes(digest[:32], "little")
+    scalar &= (1 << 254) - 8
+    scalar |= 1 << 254
+    x, y, z, _ = ed_mul(scalar, ED_BASE)
+    x, y = x * pow(z, ED_P - 2, ED_P) % ED_P, y * pow(z, ED_P - 2, ED_P) % ED_P
+    return (y | (x & 1) << 255).to_bytes(32, "little")
+
+
+def is_on_curve(raw):
+    y = int.from_bytes(raw, "little")
+    sign, y = y >> 255, y & ((1 << 255) - 1)
+    return y < ED_P and ed_recover_x(y, sign) is not None
+
+
+def find_program_address(seeds, program_id):
+    for bump in range(255, -1, -1):
+        candidate = hashlib.sha256(
+            b"".join(seeds) + bytes([bump]) + program_id + b"ProgramDerivedAddress"
+        ).digest()
+        if not is_on_curve(candidate):
+            return candidate, bump
+    raise ValueError("no program address found")
+
+
+def associated_token_address(owner, mint):
+    return find_program_address(
+        [owner, b58decode(TOKEN_PROGRAM_ID), mint],
+        b58decode(ASSOCIATED_TOKEN_PROGRAM_ID),
+    )[0]
+
+
+def discriminator(name):
+    return hashlib.sha256(f"account:{name}".encode()).digest()[:8]
+
+
+def u128(value):
+    return value.to_bytes(16, "little")
+
+
+def rent_exempt_lamports(size):
+    return (size + 128) * 6960
+
+
+def write_fixture(name, address, data, owner=DLMM_PROGRAM_ID):
+    fixture = {
+        "pubkey": b58encode(address),
+        "account": {
+            "lamports": rent_exempt_lamports(len(data)),
+            "data": [base64.b64encode(bytes(data)).decode(), "base64"],
+            "owner": owner,
+            "executable": False,
+            "rentEpoch": 18446744073709551615,
+            "space": len(data),
+        },
+    }
+    with open(os.path.join(FIXTURES_DIR, name), "w") as f:
+        json.dump(fixture, f, indent=2)
+        f.write("\n")
+
+
+def lb_pair():
+    data = bytearray(904)
+    data[0:8] = discriminator("LbPair")
+    base = 8
+
+    # parameters
+    struct.pack_into("<HHHHIIiiHB", data, base + 0,
+                     10000, 30, 600, 5000, 7500, 150000, -443636, 443636, 500, 0)
+    # v_parameters
+    struct.pack_into("<IIi", data, base + 32, 12345, 6789, -4321)
+    struct.pack_into("<q", data, base + 48, 1_700_000_000)
+
+    data[base + 64] = 254                                 # bump_seed
+    struct.pack_into("<H", data, base + 65, 10)           # bin_step_seed
+    data[base + 67] = 0                                   # pair_type
+    struct.pack_into("<i", data, base + 68, -4321)        # active_id
+    struct.pack_into("<H", data, base + 72, 10)           # bin_step
+    data[base + 74] = 0                                   # status
+    data[base + 75] = 0                                   # require_base_factor_seed
+    struct.pack_into("<H", data, base + 76, 10000)        # base_factor_seed
+    data[base + 78] = 1                                   # activation_type
+    data[base + 79] = 0                                   # creator_pool_on_off_control
+
+    data[base + 80:base + 112] = b58decode(WSOL_MINT)     # token_x_mint
+    data[base + 112:base + 144] = b58decode(USDC_MINT)    # token_y_mint
+    data[base + 144:base + 176] = key("reserve_x")
+    data[base + 176:base + 208] = key("reserve_y")
+    struct.pack_into("<QQ", data, base + 208, 123_456_789, 987_654_321)  # protocol_fee
+
+    # reward_infos[0]; reward_infos[1] stays empty
+    reward = base + 256
+    data[reward + 0:reward + 32] = key("reward_mint_0")
+    data[reward + 32:reward + 64] = key("reward_vault_0")
+    data[reward + 64:reward + 96] = key("reward_funder_0")
+    struct.pack_into("<QQ", data, reward + 96, 86_400, 1_700_086_400)
+    data[reward + 112:reward + 128] = u128((5 << 64) | 42)
+    struct.pack_into("<QQ", data, reward + 128, 1_700_000_100, 7)
+
+    data[base + 544:base + 576] = key("oracle")
+    struct.pack_into("<Q", data, base + 576 + 7 * 8, 0x8000_0000_0000_0001)  # bin_array_bitmap[7]
+    struct.pack_into("<Q", data, base + 576 + 15 * 8, 2)                     # bin_array_bitmap[15]
+    struct.pack_into("<q", data, base + 704, 1_700_000_200)                  # last_updated_at
+    data[base + 776:base + 808] = key("base_key")
+    struct.pack_into("<QQ", data, base + 808, 123_456, 3_600)                # activation_point, pre_activation_duration
+    data[base + 840:base + 872] = key("creator")
+    data[base + 872] = 0                                                     # token_mint_x_program_flag
+    data[base + 873] = 1                                                     # token_mint_y_program_flag
+
+    write_fixture("lb_pair.json", key("lb_pair"), data)
+
+
+def position():
+    data = bytearray(7560)
+    data[0:8] = discriminator("Position")
+    base = 8
+
+    data[base + 0:base + 32] = key("lb_pair")
+    data[base + 32:base + 64] = key("position_owner")
+    for i in range(70):
+        struct.pack_into("<Q", data, base + 64 + i * 8, 1000 + i)  # liquidity_shares
+
+    rewards = base + 624
+    data[rewards + 0:rewards + 16] = u128((3 << 64) | 1)
+    data[rewards + 16:rewards + 32] = u128(2)
+    struct.pack_into("<QQ", data, rewards + 32, 11, 12)
+
+    fees = base + 3984
+    data[fees + 0:fees + 16] = u128((7 << 64) | 9)
+    data[fees + 16:fees + 32] = u128(10)
+    struct.pack_into("<QQ", data, fees + 32, 500, 600)
+    struct.pack_into("<Q", data, fees + 69 * 48 + 32, 77)           # fee_infos[69].fee_x_pending
+
+    struct.pack_into("<iiq", data, base + 7344, -4355, -4286, 1_700_000_300)
+    struct.pack_into("<QQQQ", data, base + 7360, 1111, 2222, 33, 44)
+
+    write_fixture("position.json", key("position"), data)
+
+
+def position_v2():
+    """An extended PositionV2 covering 80 bins: 70 inline plus 10 trailing bins."""
+    lower_bin_id, upper_bin_id = -4355, -4276
+    extension_bins = upper_bin_id - lower_bin_id + 1 - 70
+
+    data = bytearray(8120 + extension_bins * 112)
+    data[0:8] = discriminator("PositionV2")
+    base = 8
+
+    data[base + 0:base + 32] = key("lb_pair")
+    data[base + 32:base + 64] = key("position_owner")
+    for i in range(70):
+        data[base + 64 + i * 16:base + 80 + i * 16] = u128((1 << 64) + i)  # liquidity_shares
+
+    fees = base + 4544
+    data[fees + 0:fees + 16] = u128((7 << 64) | 9)
+    struct.pack_into("<QQ", data, fees + 32, 500, 600)
+    struct.pack_into("<Q", data, fees + 69 * 48 + 32, 77)           # fee_infos[69].fee_x_pending
+
+    struct.pack_into("<iiq", data, base + 7904, lower_bin_id, upper_bin_id, 1_700_000_300)
+    struct.pack_into("<QQQQ", data, base + 7920, 1111, 2222, 33, 44)
+    data[base + 7952:base + 7984] = key("position_operator")
+    struct.pack_into("<Q", data, base + 7984, 1_800_000_000)         # lock_release_point
+    data[base + 7993:base + 8025] = key("position_fee_owner")
+
+    # Trailing bins: liquidity_share, reward_info, fee_info
+    for i in range(extension_bins):
+        bin_data = 8120 + i * 112
+        data[bin_data:bin_data + 16] = u128(2000 + i)
+        struct.pack_into("<QQ", data, bin_data + 16 + 32, 70 + i, 0)      # reward_pendings
+        struct.pack_into("<QQ", data, bin_data + 64 + 32, 100 + i, 200 + i)  # fee_x/y_pending
+
+    write_fixture("position_v2.json", key("position_v2"), data)
+
+
+def mint(name, label, authority, supply):
+    """SPL Token mint with `authority` as its mint authority, 6 decimals"""
+    data = bytearray(82)
+    struct.pack_into("<I", data, 0, 1)
+    data[4:36] = authority
+    struct.pack_into("<QB?", data, 36, supply, 6, True)   # supply, decimals, is_initialized
+    write_fixture(name, key(label), data, owner=TOKEN_PROGRAM_ID)
+
+
+def token_account(name, address, mint_address, owner, amount):
+    """Initialized SPL Token account with no delegate or close authority"""
+    data = bytearray(165)
+    data[0:32] = mint_address
+    data[32:64] = owner
+    struct.pack_into("<Q", data, 64, amount)
+    data[108] = 1                                                    # state: initialized
+    write_fixture(name, address, data, owner=TOKEN_PROGRAM_ID)
+
+
+def collect_pool():
+    """A live DLMM pool for the collection success tests, with its fee owner and
+    every position owned by the fee vault `collect_vault_creator` opens on it.
+
+    The pair, reserves, reward vault and bin array sit at the PDAs DLMM derives
+    for them, so DLMM can sign for its reserves. The positions hold no
+    liquidity, only pending fees and rewards, so what a claim pays out is fixed
+    by the fixtures: per position, base 150/200/400 and quote 1500/2000/4000,
+    and 100 of reward 0 on the first position.
+    """
+    dlmm = b58decode(DLMM_PROGRAM_ID)
+    creator = keypair_public_key(key("collect_vault_creator"))
+    token_x, token_y, reward = key("collect_token_x_mint"), key("collect_token_y_mint"), key("collect_reward_mint")
+    bin_step = 10
+
+    lb_pair_address, bump = find_program_address(
+        [min(token_x, token_y), max(token_x, token_y), struct.pack("<H", bin_step)], dlmm
+    )
+    fee_vault = find_program_address(
+        [b"fee_vault", lb_pair_address, creator], b58decode(FEE_ROUTING_PROGRAM_ID)
+    )[0]
+    reserve_x = find_program_address([lb_pair_address, token_x], dlmm)[0]
+    reserve_y = find_program_address([lb_pair_address, token_y], dlmm)[0]
+    reward_vault = find_program_address([lb_pair_address, struct.pack("<Q", 0)], dlmm)[0]
+    bin_array = find_program_address([b"bin_array", lb_pair_address, struct.pack("<q", 0)], dlmm)[0]
+
+    mint_authority = key("collect_mint_authority")
+    mint("collect_token_x_mint.json", "collect_token_x_mint", mint_authority, 1_000_000)
+    mint("collect_token_y_mint.json", "collect_token_y_mint", mint_authority, 1_000_000)
+    mint("collect_reward_mint.json", "collect_reward_mint", mint_authority, 1_000_000)
+    token_account("collect_reserve_x.json", reserve_x, token_x, lb_pair_address, 1_000_000)
+    token_account("collect_reserve_y.json", reserve_y, token_y, lb_pair_address, 1_000_000)
+    token_account("collect_reward_vault.json", reward_vault, reward, lb_pair_address, 1_000_000)
+
+    data = bytearray(904)
+    data[0:8] = discriminator("LbPair")
+    base = 8
+    struct.pack_into("<HHHHIIiiHB", data, base + 0,
+                     10000, 30, 600, 5000, 7500, 150000, -443636, 443636, 500, 0)
+    data[base + 64] = bump                                # bump_seed
+    struct.pack_into("<H", data, base + 65, bin_step)     # bin_step_seed
+    struct.pack_into("<i", data, base + 68, 0)            # active_id
+    struct.pack_into("<H", data, base + 72, bin_step)     # bin_step
+    data[base + 80:base + 112] = token_x
+    data[base + 112:base + 144] = token_y
+    data[base + 144:base + 176] = reserve_x
+    data[base + 176:base + 208] = reserve_y
+    struct.pack_into("<QQ", data, base + 208, 300, 3_000)  # protocol_fee
+
+    # reward_infos[0], already past its end so nothing accrues during the tests
+    rewards = base + 256
+    data[rewards + 0:rewards + 32] = reward
+    data[rewards + 32:rewards + 64] = reward_vault
+    data[rewards + 64:rewards + 96] = mint_authority
+    struct.pack_into("<QQ", data, rewards + 96, 1, 1)     # reward_duration, reward_duration_end
+    struct.pack_into("<QQ", data, rewards + 128, 1, 0)    # last_update_time
+
+    data[base + 544:base + 576] = key("collect_oracle")
+    struct.pack_into("<Q", data, base + 576 + 8 * 8, 1)   # bin_array_bitmap, array 0
+    data[base + 840:base + 872] = creator
+    write_fixture("collect_lb_pair.json", lb_pair_address, data)
+
+    # The DLMM admin made the vault a claim fee operator, so it can claim protocol fees
+    data = bytearray(168)
+    data[0:8] = discriminator("ClaimFeeOperator")
+    data[8:40] = fee_vault
+    claim_fee_operator = find_program_address([b"cf_operator", fee_vault], dlmm)[0]
+    write_fixture("collect_claim_fee_operator.json", claim_fee_operator, data)
+
+    data = bytearray(10136)
+    data[0:8] = discriminator("BinArray")
+    struct.pack_into("<qB", data, 8, 0, 1)                # index, version
+    data[24:56] = lb_pair_address
+    write_fixture("collect_bin_array.json", bin_array, data)
+
+    # (bin, fee_x_pending, fee_y_pending, reward_pending) per position, all in bins 0..=69
+    pending = [
+        [(0, 100, 1_000, 70), (69, 50, 500, 30)],
+        [(10, 200, 2_000, 0)],
+        [(20, 400, 4_000, 0)],
+    ]
+    for index, bins in enumerate(pending):
+        data = bytearray(8120)
+        data[0:8] = discriminator("PositionV2")
+        data[base + 0:base + 32] = lb_pair_address
+        data[base + 32:base + 64] = fee_vault
+        for bin_offset, fee_x, fee_y, reward_pending in bins:
+            struct.pack_into("<Q", data, base + 1184 + bin_offset * 48 + 32, reward_pending)
+            struct.pack_into("<QQ", data, base + 4544 + bin_offset * 48 + 32, fee_x, fee_y)
+        struct.pack_into("<iiq", data, base + 7904, 0, 69, 1_700_000_300)
+        data[base + 7993:base + 8025] = fee_vault          # fee_owner
+        write_fixture(f"collect_position_{index}.json", key(f"collect_position_{index}"), data)
+
+    return lb_pair_address
+
+
+
+def fee_vault_v0(lb_pair_address):
+    """A `FeeVault` as the first release wrote it, 161 bytes at
+    `["fee_vault", collect_lb_pair]`, with its base and quote ATAs and a
+    position it owns with base 250 and quote 2500 pending.
+
+    Its authority is the keypair web3.js derives from the
+    `legacy_vault_authority` fixture key with `Keypair.fromSeed`, so the
+    migration test can sign as it.
+    """
+    authority = keypair_public_key(key("legacy_vault_authority"))
+    address, bump = find_program_address(
+        [b"fee_vault", lb_pair_address], b58decode(FEE_ROUTING_PROGRAM_ID)
+    )
+    token_x, token_y = key("collect_token_x_mint"), key("collect_token_y_mint")
+
+    data = bytearray(discriminator("FeeVault"))
+    data.extend(authority)
+    data.extend(lb_pair_address)
+    data.extend(token_x)
+    data.extend(token_y)
+    data.append(bump)
+    data.extend(struct.pack("<QQq", 5_000, 6_000, 1_700_000_000))   # totals, creation_time
+    assert len(data) == 161
+
+    write_fixture("fee_vault_v0.json", address, data, owner=FEE_ROUTING_PROGRAM_ID)
+
+    for side, mint_address, amount in (("base", token_x, 400), ("quote", token_y, 500)):
+        token_account(
+            f"fee_vault_v0_{side}.json",
+            associated_token_address(address, mint_address),
+            mint_address,
+            address,
+            amount,
+        )
+
+    base = 8
+    data = bytearray(8120)
+    data[0:8] = discriminator("PositionV2")
+    data[base + 0:base + 32] = lb_pair_address
+    data[base + 32:base + 64] = address
+    struct.pack_into("<QQ", data, base + 4544 + 30 * 48 + 32, 250, 2_500)  # fee_infos[30]
+    struct.pack_into("<iiq", data, base + 7904, 0, 69, 1_700_000_300)
+    data[base + 7993:base + 8025] = address                # fee_owner
+    write_fixture("fee_vault_v0_position.json", key("fee_vault_v0_position"), data)
+
+
+if __name__ == "__main__":
+    os.makedirs(FIXTURES_DIR, exist_ok=True)
+    lb_pair()
+    position()
+    position_v2()
+    fee_vault_v0(collect_pool())
@@ -15,6 +15,11 @@ if ! command -v npm &> /dev/null; then
     exit 1
 fi
 
+if [ ! -f tests/fixtures/programs/lb_clmm.so ]; then
+    echo "❌ DLMM program binary not found. Run ./scripts/fetch-dlmm-program.sh and commit it."
+    exit 1
+fi
+
 # Install dependencies
 echo "📦 Installing dependencies..."
 npm install